@@ -0,0 +1,87 @@
+//! compares saving and opening a large synthetic collection as ron and
+//! as bincode; `cargo bench --bench save_format [items]`
+use color_eyre::Result;
+use infinite_cookbook::{
+    attempt::Outcome, envelope::Format, finite_collection::FiniteCollection,
+    finite_item::FiniteItem,
+};
+use std::time::{Duration, Instant};
+
+const ITEMS: usize = 50_000;
+const PARENTS: usize = 4;
+const ATTEMPTS: usize = 200_000;
+const RUNS: u32 = 3;
+
+/// names made up of a few words each, like the real ones
+fn name(i: usize) -> String {
+    const WORDS: [&str; 8] = [
+        "Water", "Fire", "Steam", "Dragon", "Cloud", "Giant", "Volcano", "Ninja",
+    ];
+    format!("{} {} {i}", WORDS[i % 8], WORDS[i / 8 % 8])
+}
+
+fn synthetic(items: usize) -> FiniteCollection {
+    let names = (0..items).map(name).collect::<Vec<_>>();
+    let items = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let parents = (1..=PARENTS)
+                .map(|p| {
+                    let first = &names[i.saturating_sub(p)];
+                    let second = &names[(i * 31 + p) % names.len()];
+                    (first.as_str(), second.as_str())
+                })
+                .collect();
+            FiniteItem::new(name, "🧪", i % 100 == 0, parents)
+        })
+        .collect();
+    let attempts = (0..ATTEMPTS)
+        .map(|i| {
+            let first = names[i % names.len()].clone();
+            let second = names[i * 7 % names.len()].clone();
+            let outcome = if i % 3 == 0 {
+                Outcome::Nothing
+            } else {
+                Outcome::Found(names[i * 13 % names.len()].as_str().into())
+            };
+            (first, second, outcome)
+        })
+        .collect();
+    FiniteCollection::new(items, attempts, None)
+}
+
+/// the fastest of a few runs
+fn time(mut run: impl FnMut() -> Result<()>) -> Result<Duration> {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        run()?;
+        best = best.min(start.elapsed());
+    }
+    Ok(best)
+}
+
+fn main() -> Result<()> {
+    let items = std::env::args()
+        .skip(1)
+        .find_map(|argument| argument.parse().ok())
+        .unwrap_or(ITEMS);
+    let collection = synthetic(items);
+    let directory = std::env::temp_dir().join(format!("save-format-{}", std::process::id()));
+    std::fs::create_dir_all(&directory)?;
+    println!("{items} items with {PARENTS} recipes each, {ATTEMPTS} attempts, best of {RUNS}");
+    for (format, extension) in [(Format::Ron, "ron"), (Format::Bincode, "bin")] {
+        let path = directory.join(format!("collection.{extension}"));
+        let path = path.to_string_lossy();
+        let save = time(|| collection.save(&path, format, 0))?;
+        let open = time(|| FiniteCollection::open(&path).map(drop))?;
+        let size = std::fs::metadata(path.as_ref())?.len();
+        println!(
+            "{format:?}: save {save:.2?}, open {open:.2?}, {} KiB",
+            size / 1024
+        );
+    }
+    std::fs::remove_dir_all(directory)?;
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use arcstr::ArcStr;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// what happened when a pair was combined
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Outcome {
+    /// the pair made the named item
+    Found(ArcStr),
+    /// the pair made nothing, asking again is pointless
+    Nothing,
+    /// the pair kept failing, so it should be tried again next scan
+    Failed,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Found(name) => write!(f, "{name}"),
+            Self::Nothing => write!(f, "Nothing"),
+            Self::Failed => write!(f, "an error"),
+        }
+    }
+}
+
+impl Outcome {
+    /// whether the pair needs to be combined (again)
+    #[must_use]
+    pub const fn is_settled(&self) -> bool {
+        !matches!(self, Self::Failed)
+    }
+}
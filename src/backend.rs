@@ -0,0 +1,210 @@
+use async_trait::async_trait;
+use color_eyre::Result;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE, RETRY_AFTER, SERVER},
+    Client, StatusCode, Url,
+};
+use std::{
+    fmt::Debug,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    cassette::{Exchange, Recorder, ReplayBackend},
+    config::{BackendConfig, Config},
+    offline::OfflineBackend,
+    response::{Response, ResponseFailure, ResponseSuccess},
+};
+
+/// something that can tell what two items combine into,
+/// e.g. the real website, a mock or some recorded data
+#[async_trait]
+pub trait CraftBackend: Debug + Send + Sync {
+    /// what `first` and `second` make: an item, or nothing
+    /// # Errors
+    /// whenever there's no answer for the pair (yet):
+    /// - `NetworkError`, `Timeout`, `CloudflareError` and 5xx
+    ///   `UnexpectedStatus`es are worth retrying, see `is_retryable`
+    /// - `NotAllowed`, `ParsingError` and other `UnexpectedStatus`es aren't
+    /// - `Miss` if an offline backend doesn't know the pair; it says
+    ///   nothing about the pair itself
+    async fn combine(&self, first: &str, second: &str) -> Result<ResponseSuccess, ResponseFailure>;
+}
+
+/// the backend the config asks for
+/// # Errors
+/// if the backend can't be set up, e.g. a cassette is missing
+pub fn from_config(config: &Config) -> Result<Arc<dyn CraftBackend>> {
+    Ok(match &config.backend {
+        BackendConfig::Http => Arc::new(HttpBackend::new(config)?),
+        BackendConfig::Replay(path) => Arc::new(ReplayBackend::open(path)?),
+        BackendConfig::Offline(paths) => Arc::new(OfflineBackend::open(paths)?),
+    })
+}
+
+/// the default backend, asking neal.fun (or whatever
+/// the config points at) over http
+#[derive(Debug, Clone)]
+#[allow(clippy::module_name_repetitions)]
+pub struct HttpBackend {
+    client: Client,
+    url: Url,
+    recorder: Option<Arc<Recorder>>,
+}
+
+impl Default for HttpBackend {
+    fn default() -> Self {
+        Self::new(&Config::default()).expect("valid default config")
+    }
+}
+
+impl HttpBackend {
+    /// # Errors
+    /// if the url, a header, a timeout or the user agent in the config is invalid
+    pub fn new(config: &Config) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &config.headers {
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes())?,
+                HeaderValue::from_str(value)?,
+            );
+        }
+        let mut client = Client::builder()
+            .default_headers(headers)
+            .timeout(Duration::try_from_secs_f64(config.timeout)?)
+            .connect_timeout(Duration::try_from_secs_f64(config.connect_timeout)?);
+        if let Some(user_agent) = &config.user_agent {
+            client = client.user_agent(user_agent);
+        }
+        let recorder = match &config.record {
+            Some(path) => Some(Arc::new(Recorder::new(path)?)),
+            None => None,
+        };
+        Ok(Self {
+            client: client.build()?,
+            url: Url::parse(&config.url)?,
+            recorder,
+        })
+    }
+
+    /// the url asking for `first` + `second`, with both names
+    /// percent-encoded so that e.g. `&` or `#` can't break the query
+    #[must_use]
+    pub fn pair_url(&self, first: &str, second: &str) -> Url {
+        let mut url = self.url.clone();
+        url.query_pairs_mut()
+            .append_pair("first", first)
+            .append_pair("second", second);
+        url
+    }
+
+    /// asks `url` for a combination, recording the exchange if recording
+    /// # Errors
+    /// `NetworkError` if the request or reading the body fails (timeouts
+    /// included), otherwise whatever `interpret` makes of the answer
+    pub async fn get(&self, url: Url) -> Result<ResponseSuccess, ResponseFailure> {
+        let start = Instant::now();
+        let response = self.client.get(url.clone()).send().await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.text().await?;
+        if let Some(recorder) = &self.recorder {
+            recorder.record(&Exchange {
+                url: url.into(),
+                status: status.as_u16(),
+                headers: headers
+                    .iter()
+                    .map(|(name, value)| {
+                        let value = String::from_utf8_lossy(value.as_bytes());
+                        (name.to_string(), value.into_owned())
+                    })
+                    .collect(),
+                body: body.clone(),
+                elapsed: u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX),
+            });
+        }
+        interpret(status, &headers, &body)
+    }
+}
+
+/// turns whatever the server answered into a combination result
+/// # Errors
+/// - `CloudflareError` for a cloudflare challenge page, whatever the status
+/// - `NotAllowed` for a 403
+/// - `Timeout` for a 429, with its `Retry-After` seconds if it sent any
+/// - `ParsingError` if a 200 isn't a combination
+/// - `UnexpectedStatus` for anything else
+pub fn interpret(
+    status: StatusCode,
+    headers: &HeaderMap,
+    body: &str,
+) -> Result<ResponseSuccess, ResponseFailure> {
+    if is_cloudflare_challenge(status, headers, body) {
+        return Err(ResponseFailure::CloudflareError);
+    }
+    match status {
+        StatusCode::FORBIDDEN => Err(ResponseFailure::NotAllowed),
+        StatusCode::TOO_MANY_REQUESTS => {
+            // only the delay-seconds form, http dates are rare enough
+            let retry_after = headers
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+                .map(Duration::from_secs);
+            Err(ResponseFailure::Timeout(retry_after))
+        }
+        StatusCode::OK => {
+            let response = serde_json::from_str::<Response>(body)?;
+            if response.name() == "Nothing" {
+                Ok(ResponseSuccess::Nothing)
+            } else {
+                Ok(ResponseSuccess::Ok(response))
+            }
+        }
+        status => Err(ResponseFailure::UnexpectedStatus(status)),
+    }
+}
+
+/// whether cloudflare answered with a challenge or an error
+/// page instead of letting the request through to neal
+fn is_cloudflare_challenge(status: StatusCode, headers: &HeaderMap, body: &str) -> bool {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+    };
+    if header("cf-mitigated") == "challenge" {
+        return true;
+    }
+    let is_html =
+        header(CONTENT_TYPE.as_str()).contains("text/html") || body.trim_start().starts_with('<');
+    if !is_html {
+        return false;
+    }
+    let from_cloudflare = header(SERVER.as_str()).contains("cloudflare");
+    let looks_like_challenge = [
+        "cf-chl",
+        "challenge-platform",
+        "Just a moment...",
+        "Attention Required! | Cloudflare",
+        "cf-error-details",
+    ]
+    .iter()
+    .any(|marker| body.contains(marker));
+    looks_like_challenge
+        || from_cloudflare
+            && matches!(
+                status,
+                StatusCode::FORBIDDEN | StatusCode::SERVICE_UNAVAILABLE | StatusCode::OK
+            )
+}
+
+#[async_trait]
+impl CraftBackend for HttpBackend {
+    async fn combine(&self, first: &str, second: &str) -> Result<ResponseSuccess, ResponseFailure> {
+        self.get(self.pair_url(first, second)).await
+    }
+}
@@ -0,0 +1,145 @@
+use color_eyre::{eyre::eyre, Result};
+use std::{
+    fmt::Display,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::clock::unix_time;
+
+/// an earlier version of a saved file, kept next to it as
+/// `<file name>.<unix milliseconds>.bak`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Backup {
+    /// unix milliseconds at which it was replaced
+    pub timestamp: u128,
+    pub path: PathBuf,
+}
+
+impl Display for Backup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let age = unix_time().as_millis().saturating_sub(self.timestamp) / 1000;
+        let age = match age {
+            0..=59 => format!("{age} seconds"),
+            60..=3599 => format!("{} minutes", age / 60),
+            3600..=86399 => format!("{} hours", age / 3600),
+            _ => format!("{} days", age / 86400),
+        };
+        let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        write!(f, "{name} ({age} ago)")
+    }
+}
+
+/// `path` with `suffix` appended to its file name
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// the directory `path` is in, never empty
+fn parent(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// writes a file so that it's either entirely the old or the new version
+///
+/// everything goes into a temporary file first, which is synced and then
+/// renamed over `path`, so an interruption at any point leaves the old one;
+/// the old version is kept as a backup, of which the `backups` newest
+/// are kept around; with `backups` at 0, none are made and the ones
+/// already there are left alone
+/// # Errors
+/// if writing, syncing or renaming fails; `path` is left untouched then
+pub fn write_atomic(
+    path: impl AsRef<Path>,
+    backups: usize,
+    write: impl FnOnce(&mut File) -> Result<()>,
+) -> Result<()> {
+    let path = path.as_ref();
+    let temporary = with_suffix(path, ".tmp");
+    let mut file = File::create(&temporary)?;
+    write(&mut file)?;
+    file.flush()?;
+    file.sync_all()?;
+    drop(file);
+
+    if backups > 0 && path.exists() {
+        let backup = with_suffix(path, &format!(".{}.bak", unix_time().as_millis()));
+        // a hard link keeps the old version without copying it
+        if std::fs::hard_link(path, &backup).is_err() {
+            std::fs::copy(path, &backup)?;
+        }
+    }
+    std::fs::rename(&temporary, path)?;
+    sync_directory(parent(path));
+    if backups > 0 {
+        prune(path, backups)?;
+    }
+    Ok(())
+}
+
+/// makes the rename itself durable, where that's possible
+fn sync_directory(directory: &Path) {
+    if let Ok(directory) = File::open(directory) {
+        // not supported everywhere, and the data is synced either way
+        let _ = directory.sync_all();
+    }
+}
+
+/// every backup of `path`, newest first
+/// # Errors
+/// if the directory can't be read
+pub fn backups(path: impl AsRef<Path>) -> Result<Vec<Backup>> {
+    let path = path.as_ref();
+    let prefix = format!(
+        "{}.",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    );
+    let mut backups = std::fs::read_dir(parent(path))?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            let timestamp = name.strip_prefix(&prefix)?.strip_suffix(".bak")?;
+            Some(Backup {
+                timestamp: timestamp.parse().ok()?,
+                path: entry.path(),
+            })
+        })
+        .collect::<Vec<_>>();
+    backups.sort_unstable_by(|a, b| b.cmp(a));
+    Ok(backups)
+}
+
+/// removes all but the `keep` newest backups of `path`
+fn prune(path: &Path, keep: usize) -> Result<()> {
+    for backup in backups(path)?.into_iter().skip(keep) {
+        std::fs::remove_file(backup.path)?;
+    }
+    Ok(())
+}
+
+/// puts a backup back in place of `path`, itself backed up first,
+/// so that restoring can be undone as well
+/// # Errors
+/// if the backup doesn't belong to `path` or can't be copied
+pub fn restore(path: impl AsRef<Path>, backup: &Backup, backups: usize) -> Result<()> {
+    let path = path.as_ref();
+    if !self::backups(path)?.contains(backup) {
+        return Err(eyre!(
+            "{} isn't a backup of {}",
+            backup.path.display(),
+            path.display()
+        ));
+    }
+    let mut source = File::open(&backup.path)?;
+    // one more, as to not prune the backup being restored
+    write_atomic(path, backups.max(1) + 1, |file| {
+        std::io::copy(&mut source, file)?;
+        Ok(())
+    })
+}
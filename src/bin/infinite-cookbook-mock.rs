@@ -0,0 +1,19 @@
+use color_eyre::Result;
+use infinite_cookbook::mock::{MockConfig, MockServer};
+use tracing::info;
+
+const CONFIG: &str = "mock.ron";
+
+/// serves the infinite craft api locally, configured by the
+/// ron file given as the only argument (or `mock.ron`)
+#[tokio::main]
+async fn main() -> Result<()> {
+    color_eyre::install()?;
+    tracing_subscriber::fmt::init();
+    let path = std::env::args().nth(1).unwrap_or_else(|| CONFIG.into());
+    let config = MockConfig::load(path)?;
+    let (address, handle) = MockServer::new(config).start().await?;
+    info!("mock infinite craft listening on http://{address}/api/infinite-craft/pair");
+    handle.await?;
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// the browser game's progress, as kept in its `infinite-craft-data`
+/// localStorage entry
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct BrowserSave {
+    pub elements: Vec<Element>,
+    /// everything else the game keeps there, e.g. `darkMode`, passed
+    /// through untouched so that pasting an export back loses none of it
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+}
+
+/// an item as the browser game knows it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Element {
+    pub text: String,
+    /// missing for items the game couldn't find an emoji for
+    #[serde(default = "unknown_emoji")]
+    pub emoji: String,
+    /// whether it was a first discovery, `is_new` in the api
+    #[serde(default)]
+    pub discovered: bool,
+    /// anything else the game keeps about an item
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+}
+
+fn unknown_emoji() -> String {
+    "❓️".into()
+}
+
+impl BrowserSave {
+    /// # Errors
+    /// if it isn't the game's json
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// the save as the game reads it back
+    /// # Errors
+    /// if it can't be written as json, which can't happen for anything
+    /// `from_json` read
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
@@ -0,0 +1,154 @@
+use async_trait::async_trait;
+use color_eyre::Result;
+use parking_lot::Mutex;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    StatusCode, Url,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+};
+use tracing::warn;
+
+use crate::{
+    backend::{interpret, CraftBackend},
+    pair_index::sort_pair_owned,
+    response::{ResponseFailure, ResponseSuccess},
+};
+
+/// one request and whatever came back, a line in a cassette file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Exchange {
+    pub url: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    /// milliseconds from sending the request to having the whole body
+    pub elapsed: u64,
+}
+
+impl Exchange {
+    #[must_use]
+    pub fn header_map(&self) -> HeaderMap {
+        self.headers
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    HeaderName::from_bytes(name.as_bytes()).ok()?,
+                    HeaderValue::from_str(value).ok()?,
+                ))
+            })
+            .collect()
+    }
+
+    /// the pair this exchange asked for, sorted, if the url has one
+    #[must_use]
+    pub fn pair(&self) -> Option<(String, String)> {
+        let url = Url::parse(&self.url).ok()?;
+        let query = url.query_pairs().collect::<HashMap<_, _>>();
+        Some(sort_pair_owned(query.get("first")?, query.get("second")?))
+    }
+
+    /// # Errors
+    /// like a real response would
+    pub fn interpret(&self) -> Result<ResponseSuccess, ResponseFailure> {
+        let status = StatusCode::from_u16(self.status)
+            .map_err(|_| ResponseFailure::UnexpectedStatus(StatusCode::INTERNAL_SERVER_ERROR))?;
+        interpret(status, &self.header_map(), &self.body)
+    }
+}
+
+/// appends every exchange to a cassette file, as ndjson
+#[derive(Debug)]
+pub struct Recorder {
+    file: Mutex<File>,
+}
+
+impl Recorder {
+    /// # Errors
+    /// if the cassette can't be opened for appending
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// writes the exchange down, only warning if that fails,
+    /// since the response itself is fine either way
+    pub fn record(&self, exchange: &Exchange) {
+        let result = serde_json::to_string(exchange)
+            .map_err(std::io::Error::from)
+            .and_then(|line| writeln!(self.file.lock(), "{line}"));
+        if let Err(why) = result {
+            warn!("couldn't record {}: {why}", exchange.url);
+        }
+    }
+}
+
+/// answers from a cassette instead of the network
+///
+/// pairs are looked up by name, not by url, so a cassette recorded
+/// against a mirror works all the same; if a pair was recorded more
+/// than once (e.g. a 429 and then its retry), the exchanges are
+/// replayed in order, the last one repeating once they run out
+#[derive(Debug)]
+pub struct ReplayBackend {
+    exchanges: HashMap<(String, String), Mutex<VecDeque<Exchange>>>,
+}
+
+impl ReplayBackend {
+    /// # Errors
+    /// if the cassette can't be read or has a broken line
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let mut exchanges = HashMap::<_, VecDeque<_>>::new();
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let exchange: Exchange = serde_json::from_str(&line)?;
+            if let Some(pair) = exchange.pair() {
+                exchanges.entry(pair).or_default().push_back(exchange);
+            }
+        }
+        Ok(Self {
+            exchanges: exchanges
+                .into_iter()
+                .map(|(pair, exchanges)| (pair, Mutex::new(exchanges)))
+                .collect(),
+        })
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.exchanges.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.exchanges.is_empty()
+    }
+}
+
+#[async_trait]
+impl CraftBackend for ReplayBackend {
+    async fn combine(&self, first: &str, second: &str) -> Result<ResponseSuccess, ResponseFailure> {
+        let exchanges = self
+            .exchanges
+            .get(&sort_pair_owned(first, second))
+            .ok_or(ResponseFailure::Miss)?;
+        let mut exchanges = exchanges.lock();
+        let exchange = if exchanges.len() > 1 {
+            exchanges.pop_front()
+        } else {
+            exchanges.front().cloned()
+        };
+        drop(exchanges);
+        exchange.ok_or(ResponseFailure::Miss)?.interpret()
+    }
+}
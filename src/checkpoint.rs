@@ -0,0 +1,115 @@
+use arcstr::ArcStr;
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, io::Write, path::Path};
+
+use crate::{backup, collection::ScanMode, pairs::Pairs};
+
+/// what a scan session has done so far
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ScanStats {
+    pub combinations: usize,
+    pub discoveries: usize,
+    pub nothing: usize,
+    pub failures: usize,
+    /// pairs an offline backend doesn't know
+    #[serde(default)]
+    pub misses: usize,
+    /// seconds spent scanning, over every run of the session
+    pub elapsed: f64,
+}
+
+impl Display for ScanStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} combinations, {} new items, {} nothing, {} failed, {} unknown in {:.0} seconds ({:.1} minutes)",
+            self.combinations,
+            self.discoveries,
+            self.nothing,
+            self.failures,
+            self.misses,
+            self.elapsed,
+            self.elapsed / 60.0
+        )
+    }
+}
+
+/// everything needed to continue a scan exactly where it stopped,
+/// saved next to the collection while the scan runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// tells sessions apart, random
+    pub session: String,
+    pub mode: ScanMode,
+    /// every item at the start of the session, the pairs come from these
+    pub names: Vec<String>,
+    /// the items that were unexplored at the start of the session
+    pub frontier: Vec<String>,
+    /// where the pairs continue from, see `Pairs::position`
+    pub cursor: (usize, usize),
+    /// pairs already handed out but not yet answered, done first on resume
+    pub pending: Vec<(String, String)>,
+    pub stats: ScanStats,
+}
+
+impl Checkpoint {
+    #[must_use]
+    pub fn new(mode: ScanMode, names: &[ArcStr], frontier: &[ArcStr]) -> Self {
+        Self {
+            session: format!("{:016x}", fastrand::u64(..)),
+            mode,
+            names: names.iter().map(ToString::to_string).collect(),
+            frontier: frontier.iter().map(ToString::to_string).collect(),
+            cursor: (0, 0),
+            pending: vec![],
+            stats: ScanStats::default(),
+        }
+    }
+
+    /// where the checkpoint of the collection at `save_path` lives
+    #[must_use]
+    pub fn path(save_path: &str) -> String {
+        format!("{save_path}.checkpoint")
+    }
+
+    /// the pairs of this session, continuing from the cursor
+    #[must_use]
+    pub fn pairs(&self) -> Pairs {
+        let names = self.names.iter().map(ArcStr::from).collect();
+        let frontier = self.frontier.iter().map(ArcStr::from).collect::<Vec<_>>();
+        Pairs::frontier(names, &frontier).with_position(self.cursor)
+    }
+
+    /// the checkpoint at `path`, if there is one
+    /// # Errors
+    /// if the file exists but can't be read or parsed
+    pub fn open(path: impl AsRef<Path>) -> Result<Option<Self>> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let string = std::fs::read_to_string(path)?;
+        Ok(Some(ron::from_str(&string)?))
+    }
+
+    /// # Errors
+    /// if the file can't be written
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let encoded = ron::to_string(self)?;
+        backup::write_atomic(path, 0, |file| {
+            file.write_all(encoded.as_bytes())?;
+            Ok(())
+        })
+    }
+
+    /// # Errors
+    /// if the file exists but can't be removed
+    pub fn remove(path: impl AsRef<Path>) -> Result<()> {
+        match std::fs::remove_file(path) {
+            Err(why) if why.kind() != std::io::ErrorKind::NotFound => Err(why.into()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// how long it's been since the unix epoch, or zero if the clock is off
+/// by that much; saves count seconds, backups and the journal milliseconds
+#[must_use]
+pub fn unix_time() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+}
@@ -2,39 +2,95 @@
 #![allow(clippy::cast_precision_loss)]
 use arcstr::ArcStr;
 use color_eyre::Result;
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
+use futures::{stream, StreamExt};
 use itertools::Itertools;
+use parking_lot::Mutex;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use reqwest::{
-    header::{HeaderMap, HeaderValue},
-    Client, StatusCode,
+use serde::{Deserialize, Serialize};
+use serde_json::Map;
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::Display,
+    path::Path,
+    sync::Arc,
+    time::Instant,
 };
-use spinners::{Spinner, Spinners};
-use std::{fmt::Display, fs::File, io::Write};
-use tracing::info;
-use zstd::bulk::decompress;
+use tracing::{info, warn};
 
 use crate::{
+    attempt::Outcome,
+    backend::{self, CraftBackend, HttpBackend},
+    browser::{BrowserSave, Element},
+    checkpoint::{Checkpoint, ScanStats},
+    config::Config,
+    envelope::Format,
     finite_collection::FiniteCollection,
     finite_item::FiniteItem,
     item::Item,
-    response::{Response, ResponseFailure, ResponseSuccess},
+    journal::{Entry, Journal},
+    limiter::RateLimiter,
+    merge::MergeReport,
+    pair_index::{sort_pair, PairIndex},
+    response::{ResponseFailure, ResponseSuccess},
+    retry::RetryPolicy,
+    shutdown::Shutdown,
+    store::{self, Store},
 };
 
-const URL: &str = "https://neal.fun/api/infinite-craft/pair?first=FIRST&second=SECOND";
-const REFERER: &str = "https://neal.fun/infinite-craft/";
-const COOLDOWN: f64 = 0.3;
+/// which pairs a scan combines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ScanMode {
+    /// every item with every item
+    Full,
+    /// only unexplored items with every item
+    #[default]
+    Frontier,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanOptions {
+    pub mode: ScanMode,
+    /// how many requests may be in flight at once
+    pub concurrency: usize,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            mode: ScanMode::default(),
+            concurrency: 4,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Collection {
     pub items: DashMap<String, Item>,
-    client: Client,
+    /// every pair ever combined, sorted by name, and what it made
+    pub attempts: DashMap<(ArcStr, ArcStr), Outcome>,
+    index: PairIndex,
+    /// items found since the last completed scan, which
+    /// haven't been combined with everything else yet
+    pub unexplored: DashSet<ArcStr>,
+    backend: Arc<dyn CraftBackend>,
+    retry: RetryPolicy,
+    limiter: Arc<RateLimiter>,
+    save_path: String,
+    autosave_interval: usize,
+    backups: usize,
+    /// picked by the save path's extension if there is none
+    format: Option<Format>,
+    shutdown: Shutdown,
+    /// writes every change as it happens, instead of the save file
+    store: Option<Arc<dyn Store>>,
+    /// unix seconds at which it was first saved, kept across saves
+    created: Option<u64>,
 }
 
 impl Default for Collection {
     fn default() -> Self {
-        let mut headers = HeaderMap::new();
-        headers.insert("Referer", HeaderValue::from_static(REFERER));
+        let config = Config::default();
         Self {
             items: DashMap::from_iter([
                 ("Water".into(), Item::new("Water", "💧", false)),
@@ -42,10 +98,19 @@ impl Default for Collection {
                 ("Wind".into(), Item::new("Wind", "🌬️", false)),
                 ("Earth".into(), Item::new("Earth", "🌍️", false)),
             ]),
-            client: Client::builder()
-                .default_headers(headers)
-                .build()
-                .expect("valid header"),
+            attempts: DashMap::new(),
+            index: PairIndex::default(),
+            unexplored: DashSet::from_iter(["Water", "Fire", "Wind", "Earth"].map(ArcStr::from)),
+            backend: Arc::new(HttpBackend::default()),
+            retry: RetryPolicy::default(),
+            limiter: Arc::new(RateLimiter::per_second(config.cooldown.recip())),
+            save_path: config.save_path,
+            autosave_interval: config.autosave_interval,
+            backups: config.backups,
+            format: config.format,
+            shutdown: Shutdown::default(),
+            store: None,
+            created: None,
         }
     }
 }
@@ -67,44 +132,183 @@ impl Collection {
             .map(|item| (item.as_str().into(), item))
             .collect_vec();
 
-        Self {
+        let collection = Self {
+            unexplored: items.iter().map(|(_, item)| item.name()).collect(),
             items: DashMap::from_iter(items),
             ..Default::default()
+        };
+        collection.rebuild_index();
+        collection
+    }
+
+    /// replaces the backend used to combine items, e.g. with a mock
+    #[must_use]
+    pub fn with_backend(self, backend: Arc<dyn CraftBackend>) -> Self {
+        Self { backend, ..self }
+    }
+
+    pub fn set_backend(&mut self, backend: Arc<dyn CraftBackend>) {
+        self.backend = backend;
+    }
+
+    #[must_use]
+    pub fn with_retry_policy(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+
+    /// applies everything but the save path from the config, which
+    /// is only remembered for saving during scans
+    /// # Errors
+    /// if the config doesn't make a valid backend
+    pub fn with_config(self, config: &Config) -> Result<Self> {
+        Ok(Self {
+            backend: backend::from_config(config)?,
+            retry: RetryPolicy {
+                max_retries: config.retries,
+                ..self.retry
+            },
+            limiter: Arc::new(RateLimiter::per_second(config.cooldown.recip())),
+            save_path: config.save_path.clone(),
+            autosave_interval: config.autosave_interval,
+            backups: config.backups,
+            format: config.format,
+            ..self
+        })
+    }
+
+    /// lets scans stop gracefully once the shutdown is requested
+    #[must_use]
+    pub fn with_shutdown(self, shutdown: Shutdown) -> Self {
+        Self { shutdown, ..self }
+    }
+
+    /// limits requests across every scan of this collection
+    #[must_use]
+    pub fn with_rate_limit(self, requests_per_second: f64) -> Self {
+        Self {
+            limiter: Arc::new(RateLimiter::per_second(requests_per_second)),
+            ..self
         }
     }
 
-    // TODO: write
+    /// remembers what combining the pair made, so it isn't asked again
+    pub fn record(&self, first: ArcStr, second: ArcStr, outcome: Outcome) {
+        if outcome.is_settled() {
+            self.index.insert(first.clone(), second.clone());
+        }
+        self.attempts.insert(sort_pair(first, second), outcome);
+    }
+
+    /// adds a recipe to an already found item, keeping the pair index in sync
+    pub fn push_parents(&self, name: &str, first: ArcStr, second: ArcStr) {
+        if let Some(mut item) = self.items.get_mut(name) {
+            if !item.contains_parents(&first, &second) {
+                item.push_parents(first.clone(), second.clone());
+            }
+            self.index.insert(first, second);
+        }
+    }
+
+    /// recomputes the pair index from every item's parents and every
+    /// settled attempt, e.g. after loading a collection from a file
+    pub fn rebuild_index(&self) {
+        self.index.clear();
+        self.items
+            .par_iter()
+            .flat_map_iter(|item| item.parents().to_vec())
+            .for_each(|(first, second)| {
+                self.index.insert(first, second);
+            });
+        self.attempts
+            .par_iter()
+            .filter(|attempt| attempt.value().is_settled())
+            .for_each(|attempt| {
+                let (first, second) = attempt.key().clone();
+                self.index.insert(first, second);
+            });
+    }
+
+    /// saves to `path`, which may also be a database (see `store::is_database`);
+    /// if it's the one the collection was opened from, its store is told,
+    /// so e.g. the journal can be emptied
     /// # Errors
     pub fn save(&self, path: &str) -> Result<()> {
-        let path = if path.is_empty() {
-            "collection.ron"
-        } else {
-            path
-        };
-        let mut file = File::create(path)?;
-        let finite_collection = self.to_finite();
-        let encoded = ron::to_string(&finite_collection)?;
-        let compressed = zstd::bulk::compress(encoded.as_bytes(), 5)?;
-        file.write_all(&compressed)?;
-        Ok(())
+        if store::is_database(path) {
+            return match &self.store {
+                Some(store) if self.save_path == path => store.save(self),
+                _ => store::import(path, &self.to_finite()),
+            };
+        }
+        let format = self.format.unwrap_or_else(|| Format::from_path(path));
+        self.to_finite().save(path, format, self.backups)?;
+        match &self.store {
+            Some(store) if self.save_path == path => store.save(self),
+            _ => Ok(()),
+        }
+    }
+
+    /// writes everything only in the journal into the save it was opened
+    /// from, in place, without rotating its backups, e.g. right before
+    /// one of them is restored over it
+    /// # Errors
+    /// if encoding or writing fails
+    pub fn flush(&self) -> Result<()> {
+        if !store::is_database(&self.save_path) {
+            let format = self
+                .format
+                .unwrap_or_else(|| Format::from_path(&self.save_path));
+            self.to_finite().save(&self.save_path, format, 0)?;
+        }
+        self.store.as_ref().map_or(Ok(()), |store| store.save(self))
+    }
+
+    /// writes the collection as plain ron, for reading it or handing it
+    /// around, or into a database if `path` is one
+    /// # Errors
+    /// if encoding or writing fails
+    pub fn export(&self, path: &str) -> Result<()> {
+        if store::is_database(path) {
+            return store::import(path, &self.to_finite());
+        }
+        self.to_finite().export(path)
     }
 
     // TODO: write
     /// # Errors
     pub fn open(path: &str) -> Result<Self> {
-        let path = if path.is_empty() {
-            "collection.ron"
-        } else {
-            path
+        if store::is_database(path) {
+            let (store, finite) = store::open(path)?;
+            return Ok(Self {
+                store: Some(store),
+                save_path: path.into(),
+                created: finite.created(),
+                ..finite.to_collection()
+            });
+        }
+        let collection = match FiniteCollection::open(path) {
+            // one that was never saved may still have a journal
+            Err(_) if !Path::new(path).exists() => Self::default(),
+            finite => {
+                let finite = finite?;
+                Self {
+                    created: finite.created(),
+                    ..finite.to_collection()
+                }
+            }
         };
-        // reasonable decompress buffer size?
-        let capacity = usize::try_from(std::fs::metadata(path)?.len() * 10)?;
-        let decoded = std::fs::read(path)?;
-        let decompressed = decompress(&decoded, capacity)?;
-        let string = String::from_utf8(decompressed)?;
-        let finite_collection: FiniteCollection = ron::from_str(&string)?;
-        let collection = finite_collection.to_collection();
-        Ok(collection)
+        let journal = Journal::path(path);
+        let entries = Journal::entries(&journal)?;
+        if !entries.is_empty() {
+            info!("replaying {} changes from {journal}", entries.len());
+        }
+        for entry in entries {
+            collection.replay(entry);
+        }
+        Ok(Self {
+            store: Some(Arc::new(Journal::open(&journal)?)),
+            save_path: path.into(),
+            ..collection
+        })
     }
 
     #[must_use]
@@ -118,29 +322,77 @@ impl Collection {
     }
 
     pub fn add_item(&mut self, name: &str, emoji: &str) {
-        self.items
-            .insert(name.into(), Item::new(name, emoji, false));
+        self.insert_item(Item::new(name, emoji, false));
     }
 
-    // TODO: write
-    /// # Errors
-    /// # Panics
-    pub async fn get(&self, url: &str) -> Result<ResponseSuccess, ResponseFailure> {
-        let response = self.client.get(url).send().await?;
-        dbg!(&response.status());
-        match response.status() {
-            StatusCode::FORBIDDEN => Err(ResponseFailure::NotAllowed),
-            StatusCode::TOO_MANY_REQUESTS => Err(ResponseFailure::Timeout),
-            StatusCode::OK => {
-                let text = response.text().await?;
-                let response = serde_json::from_str::<Response>(&text)?;
-                if response.name() == "Nothing" {
-                    Ok(ResponseSuccess::Nothing)
-                } else {
-                    Ok(ResponseSuccess::Ok(response))
-                }
+    fn insert_item(&self, item: Item) {
+        if let Some(Err(why)) = self.store.as_ref().map(|store| store.added(&item)) {
+            warn!("couldn't store {item}: {why}");
+        }
+        self.unexplored.insert(item.name());
+        self.items.insert(item.as_str().into(), item);
+    }
+
+    /// adds every item of the browser game's save that isn't in the
+    /// collection yet, returning how many that were; items already in
+    /// it are left alone, since the browser doesn't know their recipes
+    pub fn import_browser(&mut self, save: &BrowserSave) -> usize {
+        let mut added = 0;
+        for element in &save.elements {
+            if self.items.contains_key(&element.text) {
+                continue;
             }
-            _ => todo!(),
+            self.insert_item(Item::new(&element.text, &element.emoji, element.discovered));
+            added += 1;
+        }
+        added
+    }
+
+    /// merges another collection into this one, see `merge::merge`;
+    /// save afterwards, the journal doesn't keep merges
+    pub fn merge(&mut self, other: &FiniteCollection) -> MergeReport {
+        let (merged, report) = self.to_finite().merge(other);
+        if let Some(Err(why)) = self.store.as_ref().map(|store| store.merged(&merged)) {
+            warn!("couldn't store the merge: {why}");
+        }
+        let merged = merged.to_collection();
+        self.items = merged.items;
+        self.attempts = merged.attempts;
+        self.unexplored = merged.unexplored;
+        self.rebuild_index();
+        report
+    }
+
+    /// every item laid over `save`, e.g. the one it was imported from, to
+    /// be pasted back into the browser game; what's in `save` is kept as it
+    /// is and in its order, the items it doesn't have yet are added after
+    /// it, the starting items first, like in the game, then by name
+    #[must_use]
+    pub fn to_browser(&self, save: &BrowserSave) -> BrowserSave {
+        const STARTING: [&str; 4] = ["Water", "Fire", "Wind", "Earth"];
+        let known = save
+            .elements
+            .iter()
+            .map(|element| element.text.as_str())
+            .collect::<HashSet<_>>();
+        let mut added = self
+            .items
+            .iter()
+            .filter(|item| !known.contains(item.as_str()))
+            .map(|item| Element {
+                text: item.as_str().into(),
+                emoji: item.emoji().into(),
+                discovered: item.is_new(),
+                rest: Map::new(),
+            })
+            .collect::<Vec<_>>();
+        added.sort_by_cached_key(|element| {
+            let starting = STARTING.iter().position(|name| *name == element.text);
+            (starting.unwrap_or(STARTING.len()), element.text.clone())
+        });
+        BrowserSave {
+            elements: save.elements.iter().cloned().chain(added).collect(),
+            rest: save.rest.clone(),
         }
     }
 
@@ -151,146 +403,342 @@ impl Collection {
         first: &str,
         second: &str,
     ) -> Result<ResponseSuccess, ResponseFailure> {
-        let url = URL.replace("FIRST", first).replace("SECOND", second);
-        let response = self.get(&url).await?;
-        Ok(response)
+        self.backend.combine(first, second).await
     }
 
-    // TODO: write
+    /// like `combine`, but retries with backoff according to
+    /// the retry policy as long as the failure is retryable
     /// # Errors
-    pub async fn scan(&self) -> Result<usize> {
+    /// the last failure, once it isn't retryable or the retries ran out
+    pub async fn combine_with_retry(
+        &self,
+        first: &str,
+        second: &str,
+    ) -> Result<ResponseSuccess, ResponseFailure> {
+        let mut attempt = 0;
+        loop {
+            tokio::select! {
+                () = self.limiter.acquire() => {}
+                () = self.shutdown.requested() => return Err(ResponseFailure::Interrupted),
+            }
+            let response = self.combine(first, second).await;
+            match &response {
+                Ok(_) => self.limiter.on_success(),
+                Err(ResponseFailure::Timeout(retry_after)) => {
+                    self.limiter.on_rate_limited(*retry_after);
+                }
+                Err(ResponseFailure::CloudflareError) => {
+                    warn!(
+                        "cloudflare answered with a challenge instead of a combination, \
+                        pausing every request for {} seconds; if this keeps happening, \
+                        opening https://neal.fun/infinite-craft/ in a browser might help",
+                        self.retry.cloudflare_pause.as_secs_f64()
+                    );
+                    self.limiter.pause(self.retry.cloudflare_pause);
+                }
+                Err(_) => {}
+            }
+            match response {
+                Err(why) if why.is_retryable() && attempt < self.retry.max_retries => {
+                    let delay = self
+                        .retry
+                        .delay(attempt)
+                        .max(why.retry_after().unwrap_or_default());
+                    attempt += 1;
+                    warn!(
+                        "{first} + {second} failed ({why}), retry {attempt} of {} in {:.1} seconds",
+                        self.retry.max_retries,
+                        delay.as_secs_f64()
+                    );
+                    tokio::select! {
+                        () = tokio::time::sleep(delay) => {}
+                        () = self.shutdown.requested() => return Err(ResponseFailure::Interrupted),
+                    }
+                }
+                response => return response,
+            }
+        }
+    }
+
+    /// starts a new scan session, see `resume`
+    /// # Errors
+    /// if saving the collection or the checkpoint fails
+    pub async fn scan(&self, options: ScanOptions) -> Result<ScanStats> {
+        let frontier = match options.mode {
+            ScanMode::Full => self.items.iter().map(|item| item.name()).collect_vec(),
+            ScanMode::Frontier => self
+                .unexplored
+                .iter()
+                .map(|name| name.clone())
+                .collect_vec(),
+        };
+        let names = self.items.iter().map(|item| item.name()).collect_vec();
+        let checkpoint = Checkpoint::new(options.mode, &names, &frontier);
+        self.resume(checkpoint, options.concurrency).await
+    }
+
+    /// scans the pairs of a session from where its checkpoint stopped,
+    /// saving the checkpoint next to the collection on every autosave
+    /// and removing it once the session is done
+    ///
+    /// if a shutdown is requested, no new requests are sent, the ones in
+    /// flight are waited for and both the collection and the checkpoint
+    /// are saved, so that the session can be resumed later
+    /// # Errors
+    /// if saving the collection or the checkpoint fails
+    pub async fn resume(&self, checkpoint: Checkpoint, concurrency: usize) -> Result<ScanStats> {
+        let path = Checkpoint::path(&self.save_path);
+        let started = Instant::now();
+        let mut stats = checkpoint.stats;
         let mut index = 0;
 
-        let mut total = Vec::with_capacity(self.items.len().pow(2));
+        let pairs = Mutex::new(checkpoint.pairs());
+        let in_flight = Mutex::new(HashSet::new());
+        let mut pending = checkpoint
+            .pending
+            .iter()
+            .map(|(first, second)| sort_pair(first.into(), second.into()))
+            .collect::<VecDeque<_>>();
 
-        let time_now = std::time::Instant::now();
+        let upper_bound = pending.len() + pairs.lock().remaining();
+        let eta = upper_bound as f64 / self.limiter.requests_per_second();
         info!(
-            "the list will start off with {} combinations!",
-            self.items.len().pow(2)
+            "session {}: doing at most {upper_bound} combinations, {concurrency} at a time! eta: at most {eta} seconds ({} minutes)",
+            checkpoint.session,
+            eta / 60.0
         );
 
-        let total_items = self.items.len().pow(2);
-        let mut second_index = 0;
-        // TODO: instead of adding everything to a list and then
-        // removing items from it, filter items before adding them!!!
-        // for first in &self.items {
-        //     for second in &self.items {
-        //         second_index += 1;
-        //         info!("trying {} and {}!", first.clone(), second.clone());
-        //         info!("{second_index} out of {total_items}");
-        //         if first.name() == "Nothing" || second.name() == "Nothing" {
-        //             info!("contained nothing!");
-        //             continue;
-        //         }
-        //         let (first, second) = sort_items(first.name(), second.name());
-        //         if self.already_combined(&first, &second) {
-        //             info!("already combined!");
-        //             continue;
-        //         }
-        //         info!("success!");
-        //         total.push((first.clone(), second.clone()));
-        //     }
-        // }
-
-        for first in &self.items {
-            for second in &self.items {
-                total.push((first.clone(), second.clone()));
+        let total = std::iter::from_fn(|| {
+            if self.shutdown.is_requested() {
+                return None;
             }
-        }
+            pending.pop_front().or_else(|| pairs.lock().next())
+        })
+        .filter(|(first, second)| first != "Nothing" && second != "Nothing")
+        .filter(|(first, second)| !self.already_combined(first, second))
+        .inspect(|pair| {
+            in_flight.lock().insert(pair.clone());
+        });
+
+        let snapshot = |stats: ScanStats| Checkpoint {
+            cursor: pairs.lock().position(),
+            pending: in_flight
+                .lock()
+                .iter()
+                .map(|(first, second)| (first.to_string(), second.to_string()))
+                .collect(),
+            stats: ScanStats {
+                elapsed: stats.elapsed + started.elapsed().as_secs_f64(),
+                ..stats
+            },
+            ..checkpoint.clone()
+        };
 
-        info!(
-            "gathered all items; took {} seconds!",
-            time_now.elapsed().as_secs_f64()
-        );
+        let mut responses = stream::iter(total)
+            .map(|(first, second)| async move {
+                let response = self.combine_with_retry(&first, &second).await;
+                (first, second, response)
+            })
+            .buffer_unordered(concurrency.max(1));
 
-        std::thread::sleep(std::time::Duration::from_secs(3));
+        while let Some((first, second, response)) = responses.next().await {
+            if matches!(response, Err(ResponseFailure::Interrupted)) {
+                // stays in flight, so the checkpoint keeps it pending
+                continue;
+            }
+            in_flight.lock().remove(&(first.clone(), second.clone()));
+            stats.combinations += 1;
+            match &response {
+                Err(ResponseFailure::Miss) => stats.misses += 1,
+                Err(_) => stats.failures += 1,
+                Ok(ResponseSuccess::Ok(_)) => {}
+                Ok(ResponseSuccess::Nothing) => stats.nothing += 1,
+            }
+            let before = self.len();
+            let message = self.apply(&first, &second, response);
+            stats.discoveries += self.len() - before;
+            println!("✓ {message}");
+            // counted from 1, so nothing is saved right after the first one
+            index += 1;
 
-        let mut total = total
-            .par_iter()
-            .map(|(first, second)| {
-                // info!("sorting {first} and {second}");
-                sort_items(first.name(), second.name())
-            })
-            .filter(|(first, second)| {
-                // info!("checking for combinations for {first} and {second}");
-                !self.already_combined(first, second)
-            })
-            .filter(|(first, second)| {
-                // info!("filtering away Nothing for {first} and {second}");
-                first != "Nothing" || second != "Nothing"
-            })
-            .collect::<Vec<(ArcStr, ArcStr)>>();
+            if index % 100 == 0 {
+                info!(
+                    "{index} combinations done, currently at {:.2} requests per second",
+                    self.limiter.requests_per_second()
+                );
+            }
 
-        total.sort_unstable();
-        total.dedup();
+            if self.autosave_interval > 0 && index % self.autosave_interval == 0 {
+                info!("autosaving after {index} combinations");
+                self.save(&self.save_path)?;
+                snapshot(stats).save(&path)?;
+            }
+        }
+        drop(responses);
+
+        if self.shutdown.is_requested() {
+            self.save(&self.save_path)?;
+            let checkpoint = snapshot(stats);
+            checkpoint.save(&path)?;
+            info!(
+                "scan interrupted, session {} can be resumed on the next launch",
+                checkpoint.session
+            );
+            return Ok(checkpoint.stats);
+        }
 
-        info!(
-            "filtering complete! doing {} combinations! eta: {} seconds ({} minutes)",
-            total.len(),
-            (COOLDOWN + 0.15) * total.len() as f64,
-            ((COOLDOWN + 0.15) * total.len() as f64) / 60.0
-        );
+        self.explore(&checkpoint);
+        Checkpoint::remove(&path)?;
 
-        for (first, second) in total {
-            // as to not ddos neal
-            std::thread::sleep(std::time::Duration::from_secs_f64(COOLDOWN));
-
-            let mut spin = Spinner::new(Spinners::Dots, format!("{first} + {second} = ..."));
-
-            let response = self.combine(&first, &second).await;
-
-            let message = match response {
-                Err(ResponseFailure::NetworkError(why)) => todo!(),
-                Err(ResponseFailure::ParsingError(why)) => todo!(),
-                Err(ResponseFailure::CloudflareError) => todo!(),
-                Err(ResponseFailure::Timeout) => todo!(),
-                Err(ResponseFailure::NotAllowed) => todo!(),
-                Ok(ResponseSuccess::Nothing) => format!("{first} + {second} = ❌ Nothing"),
-                Ok(ResponseSuccess::Ok(result)) => {
-                    let new = if result.is_new() { " ✨" } else { "" };
-
-                    let discovery = self.items.get_mut(result.name()).map_or_else(
-                        || {
-                            // TODO: not clone
-                            let item = result.clone().to_item_with_parents(&first, &second);
-                            self.items
-                                // TODO: FIX FIX FIX!!!!!!!!
-                                .insert(item.as_str().into(), item);
-                            " 🔎"
-                        },
-                        |mut already_found| {
-                            if !already_found.contains_parents(&first, &second) {
-                                already_found.push_parents(first.clone(), second.clone());
-                            }
-                            ""
-                        },
-                    );
-                    format!("{first} + {second} = {result}{discovery}{new}")
-                }
-            };
+        stats.elapsed += started.elapsed().as_secs_f64();
+        Ok(stats)
+    }
 
-            spin.stop_and_persist("✓", message);
+    /// marks the frontier of a finished session as explored, except for
+    /// items with a pair that's still unsettled, e.g. because it kept
+    /// failing, so the next frontier scan tries those pairs again;
+    /// everything found during the session stays unexplored as well
+    fn explore(&self, checkpoint: &Checkpoint) {
+        let names = checkpoint.names.iter().map(ArcStr::from).collect_vec();
+        for name in checkpoint.frontier.iter().map(ArcStr::from) {
+            let settled = names.iter().all(|other| {
+                // never combined, see `resume`
+                name == "Nothing" || other == "Nothing" || self.already_combined(&name, other)
+            });
+            if settled {
+                self.unexplored.remove(&name);
+            }
+        }
+    }
 
-            if index % 1000 == 0 {
-                info!("1000");
-                self.save("collection.ron")?;
+    /// adds what combining the pair made to the collection,
+    /// returning a message describing it
+    pub fn apply(
+        &self,
+        first: &ArcStr,
+        second: &ArcStr,
+        response: Result<ResponseSuccess, ResponseFailure>,
+    ) -> String {
+        match response {
+            // the offline backends just don't know it, which says nothing
+            // about the pair, so it's neither recorded nor stored
+            Err(ResponseFailure::Miss) => format!("{first} + {second} = ❔ unknown"),
+            // nor does stopping before an answer came
+            Err(ResponseFailure::Interrupted) => format!("{first} + {second} = ⏹️ interrupted"),
+            Err(why) => {
+                warn!("giving up on {first} + {second}: {why}");
+                self.record(first.clone(), second.clone(), Outcome::Failed);
+                self.store_combined(first, second, &Outcome::Failed, None);
+                format!("{first} + {second} = ⚠️ {why}")
+            }
+            Ok(ResponseSuccess::Nothing) => {
+                self.record(first.clone(), second.clone(), Outcome::Nothing);
+                self.store_combined(first, second, &Outcome::Nothing, None);
+                format!("{first} + {second} = ❌ Nothing")
+            }
+            Ok(ResponseSuccess::Ok(result)) => {
+                let name = ArcStr::from(result.name());
+                let outcome = Outcome::Found(name);
+                self.record(first.clone(), second.clone(), outcome.clone());
+                let new = if result.is_new() { " ✨" } else { "" };
+
+                let discovery = if self.items.contains_key(result.name()) {
+                    self.push_parents(result.name(), first.clone(), second.clone());
+                    self.store_combined(first, second, &outcome, None);
+                    ""
+                } else {
+                    // TODO: not clone
+                    let item = result.clone().to_item_with_parents(first, second);
+                    self.store_combined(first, second, &outcome, Some(&item));
+                    self.unexplored.insert(item.name());
+                    self.items
+                        // TODO: FIX FIX FIX!!!!!!!!
+                        .insert(item.as_str().into(), item);
+                    " 🔎"
+                };
+                format!("{first} + {second} = {result}{discovery}{new}")
             }
-            index += 1;
         }
+    }
 
-        Ok(index)
+    /// applies a journal entry the way `apply` did before, without
+    /// writing it to the store again
+    fn replay(&self, entry: Entry) {
+        match entry {
+            Entry::Added {
+                name,
+                emoji,
+                is_new,
+                ..
+            } => {
+                // e.g. already saved when the journal couldn't be emptied,
+                // it keeps the recipes found for it since
+                if !self.items.contains_key(&name) {
+                    self.unexplored.insert(name.as_str().into());
+                    self.items
+                        .insert(name.clone(), Item::new(&name, &emoji, is_new));
+                }
+            }
+            Entry::Combined {
+                first,
+                second,
+                outcome,
+                emoji,
+                is_new,
+                ..
+            } => {
+                let (first, second) = (ArcStr::from(first), ArcStr::from(second));
+                if let Outcome::Found(name) = &outcome {
+                    if self.items.contains_key(name.as_str()) {
+                        self.push_parents(name, first.clone(), second.clone());
+                    } else {
+                        let emoji = emoji.as_deref().unwrap_or("❓️");
+                        let item = Item::new_with_parents(name, emoji, is_new, &first, &second);
+                        self.unexplored.insert(item.name());
+                        self.items.insert(name.to_string(), item);
+                    }
+                }
+                self.record(first, second, outcome);
+            }
+        }
+    }
+
+    /// writes a combination to the store, if there is one; failing
+    /// to is only logged, the next save writes it either way
+    fn store_combined(
+        &self,
+        first: &str,
+        second: &str,
+        outcome: &Outcome,
+        discovered: Option<&Item>,
+    ) {
+        if let Some(Err(why)) = self
+            .store
+            .as_ref()
+            .map(|store| store.combined(first, second, outcome, discovered))
+        {
+            warn!("couldn't store {first} + {second}: {why}");
+        }
     }
 
     #[must_use]
-    pub fn already_combined(&self, first: &str, second: &str) -> bool {
-        self.items
-            .iter()
-            .any(|item| item.contains_parents(first, second))
+    pub fn already_combined(&self, first: &ArcStr, second: &ArcStr) -> bool {
+        self.index.contains(first, second)
     }
 
     #[must_use]
     pub fn to_finite(&self) -> FiniteCollection {
-        FiniteCollection::new(self.to_finite_items())
+        FiniteCollection::new(
+            self.to_finite_items(),
+            self.to_finite_attempts(),
+            Some(
+                self.unexplored
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect(),
+            ),
+        )
+        .with_created(self.created)
     }
 
     #[must_use]
@@ -309,15 +757,19 @@ impl Collection {
             .map(|(_, item)| item.to_finite())
             .collect()
     }
-}
 
-fn sort_items(first: ArcStr, second: ArcStr) -> (ArcStr, ArcStr) {
-    // TODO: improve
-    let mut names = [first.as_str(), second.as_str()];
-    names.sort_unstable();
-    if *names[0] == first {
-        (first, second)
-    } else {
-        (second, first)
+    #[must_use]
+    pub fn to_finite_attempts(&self) -> Vec<(String, String, Outcome)> {
+        self.attempts
+            .iter()
+            .map(|attempt| {
+                let (first, second) = attempt.key();
+                (
+                    first.to_string(),
+                    second.to_string(),
+                    attempt.value().clone(),
+                )
+            })
+            .collect()
     }
 }
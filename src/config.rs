@@ -0,0 +1,110 @@
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path, time::Duration};
+
+use crate::envelope::Format;
+
+/// where combinations come from
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[allow(clippy::module_name_repetitions)]
+pub enum BackendConfig {
+    /// asking `url` over http
+    #[default]
+    Http,
+    /// answering from the cassette at this path, without the network
+    Replay(String),
+    /// answering from the recipes in these saved collections
+    Offline(Vec<String>),
+}
+
+/// everything that can be changed without recompiling, read from
+/// a ron file at startup; anything missing from it is defaulted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub backend: BackendConfig,
+    /// appends every http exchange to the cassette at this path
+    pub record: Option<String>,
+    /// the pair endpoint, `first` and `second` get appended as query
+    pub url: String,
+    /// sent with every request, the referer included
+    pub headers: BTreeMap<String, String>,
+    pub user_agent: Option<String>,
+    /// seconds until a whole request times out
+    pub timeout: f64,
+    /// seconds until connecting times out
+    pub connect_timeout: f64,
+    /// seconds between requests, at least at first
+    pub cooldown: f64,
+    /// how many requests may be in flight at once
+    pub concurrency: usize,
+    /// retries for a failing pair before it's skipped
+    pub retries: u32,
+    pub save_path: String,
+    /// combinations between saves during a scan
+    pub autosave_interval: usize,
+    /// earlier versions of the save kept around, 0 for none
+    pub backups: usize,
+    /// what saves are encoded as; if none, bincode for `.bin`
+    /// save paths and ron for anything else
+    pub format: Option<Format>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            backend: BackendConfig::Http,
+            record: None,
+            url: "https://neal.fun/api/infinite-craft/pair".into(),
+            headers: BTreeMap::from([(
+                "Referer".into(),
+                "https://neal.fun/infinite-craft/".into(),
+            )]),
+            user_agent: None,
+            timeout: 30.0,
+            connect_timeout: 10.0,
+            cooldown: 0.3,
+            concurrency: 4,
+            retries: 5,
+            save_path: "collection.ron".into(),
+            autosave_interval: 1000,
+            backups: 5,
+            format: None,
+        }
+    }
+}
+
+impl Config {
+    /// reads the config at `path`, or the defaults if there is none
+    /// # Errors
+    /// if the file exists but can't be read or parsed, or has nonsense in it
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let string = std::fs::read_to_string(path)?;
+        let config: Self = ron::from_str(&string)?;
+        config
+            .validate()
+            .wrap_err_with(|| format!("invalid {}", path.display()))?;
+        Ok(config)
+    }
+
+    /// # Errors
+    /// if a timeout or the cooldown isn't a usable number of seconds
+    pub fn validate(&self) -> Result<()> {
+        for (name, seconds) in [
+            ("timeout", self.timeout),
+            ("connect_timeout", self.connect_timeout),
+            ("cooldown", self.cooldown),
+        ] {
+            Duration::try_from_secs_f64(seconds)
+                .map_err(|why| eyre!("{name} of {seconds} seconds: {why}"))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use crate::{attempt::Outcome, finite_collection::FiniteCollection, pair_index::sort_pair_owned};
+
+/// an item that's only in one of the collections
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffItem {
+    pub name: String,
+    pub emoji: String,
+}
+
+/// a recipe for an item both collections have, but only the newer one knows
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffRecipe {
+    pub item: String,
+    pub first: String,
+    pub second: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffEmoji {
+    pub name: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// a pair the newer collection has an answer for, and the older doesn't
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffAttempt {
+    pub first: String,
+    pub second: String,
+    pub outcome: Outcome,
+}
+
+/// what changed from one collection to another, e.g. what someone
+/// else's scan found that this collection doesn't have yet
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Diff {
+    pub added: Vec<DiffItem>,
+    pub removed: Vec<DiffItem>,
+    pub recipes: Vec<DiffRecipe>,
+    pub emoji: Vec<DiffEmoji>,
+    pub attempts: Vec<DiffAttempt>,
+}
+
+impl Diff {
+    /// everything that's in `after` but not `before`, and the other way around for items
+    #[must_use]
+    pub fn new(before: &FiniteCollection, after: &FiniteCollection) -> Self {
+        let old = before
+            .items()
+            .iter()
+            .map(|item| (item.name(), item))
+            .collect::<HashMap<_, _>>();
+        let new = after
+            .items()
+            .iter()
+            .map(|item| (item.name(), item))
+            .collect::<HashMap<_, _>>();
+        let mut diff = Self::default();
+
+        for item in after.items() {
+            let Some(old) = old.get(item.name()) else {
+                diff.added.push(DiffItem {
+                    name: item.name().into(),
+                    emoji: item.emoji().into(),
+                });
+                continue;
+            };
+            if old.emoji() != item.emoji() {
+                diff.emoji.push(DiffEmoji {
+                    name: item.name().into(),
+                    before: old.emoji().into(),
+                    after: item.emoji().into(),
+                });
+            }
+            let known = old
+                .parents()
+                .into_iter()
+                .map(|(first, second)| sort_pair_owned(first, second))
+                .collect::<HashSet<_>>();
+            for (first, second) in item.parents() {
+                if !known.contains(&sort_pair_owned(first, second)) {
+                    diff.recipes.push(DiffRecipe {
+                        item: item.name().into(),
+                        first: first.into(),
+                        second: second.into(),
+                    });
+                }
+            }
+        }
+        for item in before.items() {
+            if !new.contains_key(item.name()) {
+                diff.removed.push(DiffItem {
+                    name: item.name().into(),
+                    emoji: item.emoji().into(),
+                });
+            }
+        }
+
+        let settled = before
+            .attempts()
+            .iter()
+            .filter(|(_, _, outcome)| outcome.is_settled())
+            .map(|(first, second, _)| sort_pair_owned(first, second))
+            .collect::<HashSet<_>>();
+        for (first, second, outcome) in after.attempts() {
+            if outcome.is_settled() && !settled.contains(&sort_pair_owned(first, second)) {
+                diff.attempts.push(DiffAttempt {
+                    first: first.clone(),
+                    second: second.clone(),
+                    outcome: outcome.clone(),
+                });
+            }
+        }
+
+        diff.added.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        diff.removed.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        diff.recipes.sort_unstable_by(|a, b| {
+            (&a.item, &a.first, &a.second).cmp(&(&b.item, &b.first, &b.second))
+        });
+        diff.emoji.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        diff.attempts
+            .sort_unstable_by(|a, b| (&a.first, &a.second).cmp(&(&b.first, &b.second)));
+        diff
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.recipes.is_empty()
+            && self.emoji.is_empty()
+            && self.attempts.is_empty()
+    }
+
+    /// the diff as pretty json, for other tools to read
+    /// # Errors
+    /// if it can't be written as json, which can't happen while
+    /// everything in it is made of strings
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for DiffItem { name, emoji } in &self.added {
+            writeln!(f, "+ {emoji} {name}")?;
+        }
+        for DiffItem { name, emoji } in &self.removed {
+            writeln!(f, "- {emoji} {name}")?;
+        }
+        for DiffEmoji {
+            name,
+            before,
+            after,
+        } in &self.emoji
+        {
+            writeln!(f, "~ {name} {before} → {after}")?;
+        }
+        for DiffRecipe {
+            item,
+            first,
+            second,
+        } in &self.recipes
+        {
+            writeln!(f, "~ {first} + {second} = {item}")?;
+        }
+        for DiffAttempt {
+            first,
+            second,
+            outcome,
+        } in &self.attempts
+        {
+            writeln!(f, "? {first} + {second} = {outcome}")?;
+        }
+        write!(
+            f,
+            "{} added, {} removed, {} emoji changed, {} new recipes, {} newly attempted pairs",
+            self.added.len(),
+            self.removed.len(),
+            self.emoji.len(),
+            self.recipes.len(),
+            self.attempts.len()
+        )
+    }
+}
@@ -0,0 +1,252 @@
+use color_eyre::{eyre::eyre, Result};
+use serde::{
+    de::{self, IgnoredAny, MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use std::{io::BufRead, path::Path};
+
+use crate::{clock::unix_time, finite_collection::FiniteCollection};
+
+/// tells saved collections apart from any other ron file
+pub const MAGIC: &str = "infinite-cookbook";
+
+/// the version collections are saved as
+///
+/// when the format changes: bump this, freeze the previous shape in
+/// its own struct, add a variant for it to `Versioned` and teach
+/// `Versioned::into_current` to turn it into the next one
+pub const VERSION: u32 = 1;
+
+/// how collections are encoded, before they're compressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Format {
+    /// readable, and what every version before bincode saved
+    #[default]
+    Ron,
+    /// much smaller and faster for large collections
+    Bincode,
+}
+
+impl Format {
+    /// bincode for `.bin` and `.bincode` files, ron for anything else
+    #[must_use]
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path
+            .as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+        {
+            Some("bin" | "bincode") => Self::Bincode,
+            _ => Self::Ron,
+        }
+    }
+
+    /// tells the formats apart by their first bytes: ron always starts
+    /// with a parenthesis, bincode with the length of the magic
+    #[must_use]
+    pub fn detect(bytes: &[u8]) -> Self {
+        match bytes.iter().find(|byte| !byte.is_ascii_whitespace()) {
+            Some(b'(') | None => Self::Ron,
+            Some(_) => Self::Bincode,
+        }
+    }
+}
+
+/// what's written to disk: the collection and some facts about the file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope<C> {
+    pub magic: String,
+    pub version: u32,
+    /// unix seconds at which the collection was first saved
+    pub created: u64,
+    /// unix seconds at which the collection was last saved
+    pub modified: u64,
+    /// the version of infinite cookbook that last saved it
+    pub tool: String,
+    pub collection: C,
+}
+
+impl<C> Envelope<C> {
+    /// wraps a collection to be saved now, keeping when it was created
+    #[must_use]
+    pub fn new(collection: C, created: Option<u64>) -> Self {
+        let now = unix_time().as_secs();
+        Self {
+            magic: MAGIC.into(),
+            version: VERSION,
+            created: created.unwrap_or(now),
+            modified: now,
+            tool: env!("CARGO_PKG_VERSION").into(),
+            collection,
+        }
+    }
+}
+
+/// everything in an envelope but the collection; bincode reads fields
+/// in order, so they're the same as the envelope's
+#[derive(Debug, Default, Deserialize)]
+struct Header {
+    magic: String,
+    version: u32,
+    created: u64,
+    modified: u64,
+    tool: String,
+}
+
+impl Header {
+    /// anything without the magic predates the envelope
+    fn version(&self) -> u32 {
+        if self.magic == MAGIC {
+            self.version
+        } else {
+            0
+        }
+    }
+
+    /// refuses versions newer than this one before their collection,
+    /// whose shape may have changed, is read
+    fn check(&self) -> Result<()> {
+        let version = self.version();
+        if version > VERSION {
+            return Err(eyre!(
+                "this collection was saved by infinite cookbook {} in format version {version}, \
+                 which is newer than this version understands ({VERSION}), please update",
+                self.tool
+            ));
+        }
+        Ok(())
+    }
+
+    fn wrap(self, collection: FiniteCollection) -> Envelope<FiniteCollection> {
+        Envelope {
+            magic: self.magic,
+            version: self.version,
+            created: self.created,
+            modified: self.modified,
+            tool: self.tool,
+            collection,
+        }
+    }
+}
+
+/// every version collections have been saved as
+#[derive(Debug)]
+enum Versioned {
+    /// a bare collection without an envelope, from before versioning
+    V0(FiniteCollection),
+    V1(Envelope<FiniteCollection>),
+}
+
+impl Versioned {
+    /// upgrades one version at a time until it's the current one
+    fn into_current(self) -> Envelope<FiniteCollection> {
+        match self {
+            // nothing tells when it was created, so it counts from now on
+            Self::V0(collection) => Self::V1(Envelope::new(collection, None)).into_current(),
+            Self::V1(envelope) => envelope,
+        }
+    }
+}
+
+/// ron saves of every version are structs, told apart by their fields
+/// as they're read, so a save is parsed once whatever its version
+impl<'de> Deserialize<'de> for Versioned {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        const FIELDS: &[&str] = &[
+            "magic",
+            "version",
+            "created",
+            "modified",
+            "tool",
+            "collection",
+            "items",
+            "attempts",
+            "unexplored",
+        ];
+        deserializer.deserialize_struct("Versioned", FIELDS, VersionedVisitor)
+    }
+}
+
+/// the fields of every version's outermost struct
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum Field {
+    Magic,
+    Version,
+    Created,
+    Modified,
+    Tool,
+    Collection,
+    Items,
+    Attempts,
+    Unexplored,
+    #[serde(other)]
+    Other,
+}
+
+struct VersionedVisitor;
+
+impl<'de> Visitor<'de> for VersionedVisitor {
+    type Value = Versioned;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a saved collection")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Versioned, A::Error> {
+        let mut header = Header::default();
+        let mut collection = None;
+        let mut items = None;
+        let mut attempts = None;
+        let mut unexplored = None;
+        while let Some(field) = map.next_key()? {
+            match field {
+                Field::Magic => header.magic = map.next_value()?,
+                Field::Version => header.version = map.next_value()?,
+                Field::Created => header.created = map.next_value()?,
+                Field::Modified => header.modified = map.next_value()?,
+                Field::Tool => header.tool = map.next_value()?,
+                // the envelope is saved with its header first
+                Field::Collection => {
+                    header.check().map_err(de::Error::custom)?;
+                    collection = Some(map.next_value()?);
+                }
+                Field::Items => items = Some(map.next_value()?),
+                Field::Attempts => attempts = Some(map.next_value()?),
+                Field::Unexplored => unexplored = map.next_value()?,
+                Field::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        header.check().map_err(de::Error::custom)?;
+        match (header.version(), collection, items) {
+            (0, _, Some(items)) => Ok(Versioned::V0(FiniteCollection::new(
+                items,
+                attempts.unwrap_or_default(),
+                unexplored,
+            ))),
+            (0, _, None) => Err(de::Error::missing_field("items")),
+            (_, Some(collection), _) => Ok(Versioned::V1(header.wrap(collection))),
+            (_, None, _) => Err(de::Error::missing_field("collection")),
+        }
+    }
+}
+
+/// reads a saved collection of any version and either format, upgrading
+/// it if it's older; it's decoded straight from `reader`
+/// # Errors
+/// if it's saved by a newer version, or doesn't parse as the version it claims to be
+pub fn read(mut reader: impl BufRead) -> Result<Envelope<FiniteCollection>> {
+    let versioned = match Format::detect(reader.fill_buf()?) {
+        // ron can't parse a stream, it reads it all first either way
+        Format::Ron => ron::de::from_reader(reader)?,
+        // there was no bincode before the envelope
+        Format::Bincode => {
+            let header: Header = bincode::deserialize_from(&mut reader)?;
+            header.check()?;
+            Versioned::V1(header.wrap(bincode::deserialize_from(reader)?))
+        }
+    };
+    Ok(versioned.into_current())
+}
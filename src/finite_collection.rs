@@ -1,14 +1,38 @@
 use arcstr::ArcStr;
+use color_eyre::Result;
 use derive_new::new;
-use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+};
 
-use crate::{collection::Collection, finite_item::FiniteItem, item::Item};
+use crate::{
+    attempt::Outcome,
+    backup,
+    collection::Collection,
+    envelope::{self, Envelope, Format},
+    finite_item::FiniteItem,
+    merge::{self, MergeReport},
+};
+
+/// the first bytes of every zstd frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
 
 #[derive(Debug, Serialize, Deserialize, new, Clone)]
 pub struct FiniteCollection {
     items: Vec<FiniteItem>,
+    // missing in collections saved before attempts were recorded
+    #[serde(default)]
+    attempts: Vec<(String, String, Outcome)>,
+    // missing in collections saved before frontier scans,
+    // in which case every item counts as unexplored
+    #[serde(default)]
+    unexplored: Option<Vec<String>>,
+    /// kept in the envelope rather than the collection itself
+    #[serde(skip)]
+    #[new(default)]
+    created: Option<u64>,
 }
 
 impl std::fmt::Display for FiniteCollection {
@@ -18,6 +42,101 @@ impl std::fmt::Display for FiniteCollection {
 }
 
 impl FiniteCollection {
+    /// saves atomically in `format`, keeping the `backups` newest earlier versions
+    /// # Errors
+    /// if encoding or writing fails, the old file is left untouched then
+    pub fn save(&self, path: &str, format: Format, backups: usize) -> Result<()> {
+        let path = if path.is_empty() {
+            "collection.ron"
+        } else {
+            path
+        };
+        let envelope = Envelope::new(self, self.created);
+        backup::write_atomic(path, backups, |file| {
+            let mut encoder = zstd::Encoder::new(BufWriter::new(file), 5)?;
+            match format {
+                Format::Ron => ron::ser::to_writer(&mut encoder, &envelope)?,
+                Format::Bincode => bincode::serialize_into(&mut encoder, &envelope)?,
+            }
+            encoder.finish()?.flush()?;
+            Ok(())
+        })
+    }
+
+    /// writes the collection as plain, readable ron, without compressing
+    /// it; it opens like any other save
+    /// # Errors
+    /// if encoding or writing fails
+    pub fn export(&self, path: &str) -> Result<()> {
+        let envelope = Envelope::new(self, self.created);
+        let pretty = ron::ser::PrettyConfig::new().depth_limit(3);
+        backup::write_atomic(path, 0, |file| {
+            let mut writer = BufWriter::new(file);
+            ron::ser::to_writer_pretty(&mut writer, &envelope, pretty)?;
+            writer.flush()?;
+            Ok(())
+        })
+    }
+
+    /// opens a collection saved by any version in any format, compressed or
+    /// not, upgrading it if it's older
+    /// # Errors
+    /// if it can't be read, or is saved by a newer version
+    pub fn open(path: &str) -> Result<Self> {
+        let path = if path.is_empty() {
+            "collection.ron"
+        } else {
+            path
+        };
+        let mut reader = BufReader::new(File::open(path)?);
+        let envelope = if reader.fill_buf()?.starts_with(&ZSTD_MAGIC) {
+            // streamed, so there's no guessing how well it compressed
+            envelope::read(BufReader::new(zstd::Decoder::with_buffer(reader)?))?
+        } else {
+            // e.g. an export
+            envelope::read(reader)?
+        };
+        Ok(Self {
+            created: Some(envelope.created),
+            ..envelope.collection
+        })
+    }
+
+    /// this collection with everything from `other` it doesn't have yet,
+    /// and where the two disagree, see `merge::merge`
+    #[must_use]
+    pub fn merge(&self, other: &Self) -> (Self, MergeReport) {
+        merge::merge(self, other)
+    }
+
+    /// keeps when the collection was first saved across saves
+    #[must_use]
+    pub fn with_created(self, created: Option<u64>) -> Self {
+        Self { created, ..self }
+    }
+
+    /// unix seconds at which the collection was first saved, if it was
+    #[must_use]
+    pub const fn created(&self) -> Option<u64> {
+        self.created
+    }
+
+    #[must_use]
+    pub fn items(&self) -> &[FiniteItem] {
+        &self.items
+    }
+
+    /// none for collections saved before frontier scans
+    #[must_use]
+    pub fn unexplored(&self) -> Option<&[String]> {
+        self.unexplored.as_deref()
+    }
+
+    #[must_use]
+    pub fn attempts(&self) -> &[(String, String, Outcome)] {
+        &self.attempts
+    }
+
     // pub fn to_items(&self) -> Vec<Item> {
     //     self.items.iter().map(FiniteItem::to_item).collect_vec()
     // }
@@ -50,6 +169,23 @@ impl FiniteCollection {
                     .insert(finite_item.name().into(), item.clone());
             }
         }
+        for (first, second, outcome) in &self.attempts {
+            collection.record(first.into(), second.into(), outcome.clone());
+        }
+        collection.rebuild_index();
+        collection.unexplored.clear();
+        match &self.unexplored {
+            Some(unexplored) => {
+                for name in unexplored {
+                    collection.unexplored.insert(name.into());
+                }
+            }
+            None => {
+                for item in &collection.items {
+                    collection.unexplored.insert(item.name());
+                }
+            }
+        }
         collection
     }
 
@@ -1,4 +1,3 @@
-use arcstr::ArcStr;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
@@ -49,7 +48,17 @@ impl FiniteItem {
     }
 
     #[must_use]
-    pub fn has_parents(&self) -> bool {
+    pub fn emoji(&self) -> &str {
+        &self.emoji
+    }
+
+    #[must_use]
+    pub const fn is_new(&self) -> bool {
+        self.is_new
+    }
+
+    #[must_use]
+    pub const fn has_parents(&self) -> bool {
         !self.parents.is_empty()
     }
 
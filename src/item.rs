@@ -59,6 +59,11 @@ impl Item {
         &self.name
     }
 
+    #[must_use]
+    pub fn emoji(&self) -> &str {
+        &self.emoji
+    }
+
     #[must_use]
     pub const fn is_new(&self) -> bool {
         self.is_new
@@ -0,0 +1,136 @@
+use color_eyre::Result;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+};
+use tracing::warn;
+
+use crate::{
+    attempt::Outcome, clock::unix_time, collection::Collection,
+    finite_collection::FiniteCollection, item::Item, store::Store,
+};
+
+/// something that happened to a collection since it was last saved,
+/// a line in the journal
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Entry {
+    Combined {
+        first: String,
+        second: String,
+        outcome: Outcome,
+        /// the emoji of what it made, if that's new to the collection
+        emoji: Option<String>,
+        is_new: bool,
+        /// unix milliseconds
+        timestamp: u128,
+    },
+    Added {
+        name: String,
+        emoji: String,
+        #[serde(default)]
+        is_new: bool,
+        /// unix milliseconds
+        timestamp: u128,
+    },
+}
+
+/// a write-ahead log of every combination, as ndjson next to the save
+///
+/// nothing but the requests in flight is lost if the process is killed
+/// between saves; it's replayed when the collection is opened and emptied
+/// once it's saved again
+#[derive(Debug)]
+pub struct Journal {
+    file: Mutex<File>,
+}
+
+impl Journal {
+    /// where the journal of the collection at `save_path` lives
+    #[must_use]
+    pub fn path(save_path: &str) -> String {
+        format!("{save_path}.journal")
+    }
+
+    /// # Errors
+    /// if the journal can't be opened for appending
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// every entry in the journal at `path`, oldest first; a line that
+    /// doesn't parse, e.g. one cut off by a crash, is skipped
+    /// # Errors
+    /// if the file exists but can't be read
+    pub fn entries(path: impl AsRef<Path>) -> Result<Vec<Entry>> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let mut entries = vec![];
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(why) => warn!("skipping a broken journal line: {why}"),
+            }
+        }
+        Ok(entries)
+    }
+
+    fn append(&self, entry: &Entry) -> Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        // one write per line, so a line is never interleaved with another
+        self.file.lock().write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl Store for Journal {
+    fn combined(
+        &self,
+        first: &str,
+        second: &str,
+        outcome: &Outcome,
+        discovered: Option<&Item>,
+    ) -> Result<()> {
+        self.append(&Entry::Combined {
+            first: first.into(),
+            second: second.into(),
+            outcome: outcome.clone(),
+            emoji: discovered.map(|item| item.emoji().into()),
+            is_new: discovered.is_some_and(Item::is_new),
+            timestamp: unix_time().as_millis(),
+        })
+    }
+
+    fn added(&self, item: &Item) -> Result<()> {
+        self.append(&Entry::Added {
+            name: item.as_str().into(),
+            emoji: item.emoji().into(),
+            is_new: item.is_new(),
+            timestamp: unix_time().as_millis(),
+        })
+    }
+
+    /// too much to journal; a merge is followed by a save, and until then
+    /// it can simply be merged again
+    fn merged(&self, _collection: &FiniteCollection) -> Result<()> {
+        Ok(())
+    }
+
+    /// everything in the journal is in the save now, so it's emptied
+    fn save(&self, _collection: &Collection) -> Result<()> {
+        self.file.lock().set_len(0)?;
+        Ok(())
+    }
+}
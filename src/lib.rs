@@ -0,0 +1,27 @@
+pub mod attempt;
+pub mod backend;
+pub mod backup;
+pub mod browser;
+pub mod cassette;
+pub mod checkpoint;
+pub mod clock;
+pub mod collection;
+pub mod config;
+pub mod diff;
+pub mod envelope;
+pub mod finite_collection;
+pub mod finite_item;
+pub mod item;
+pub mod journal;
+pub mod limiter;
+pub mod merge;
+pub mod mock;
+pub mod offline;
+pub mod pair_index;
+pub mod pairs;
+pub mod response;
+pub mod retry;
+pub mod shutdown;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod store;
@@ -0,0 +1,234 @@
+use parking_lot::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::info;
+
+/// successes in a row before the rate creeps back up
+const INCREASE_AFTER: u32 = 20;
+/// requests per second added after enough successes
+const INCREASE_BY: f64 = 0.25;
+/// what the rate is multiplied with on every 429
+const DECREASE_BY: f64 = 0.5;
+/// longer than anything is ever waited for, like tokio's own "far future"
+// `Duration::from_hours` is too recent to rely on
+#[allow(clippy::duration_suboptimal_units)]
+const FOREVER: Duration = Duration::from_secs(86400 * 365 * 30);
+/// the rate never drops below this many requests per second, unless it
+/// started out slower
+const MIN_RATE: f64 = 0.05;
+
+/// hands out evenly spaced slots to every request, no matter how many
+/// are in flight at once, as to not ddos neal
+///
+/// the rate adapts to feedback: it's halved on every 429 (waiting for
+/// `Retry-After` if given) and goes back up bit by bit after a run of
+/// successes, but never above the rate it started with
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct RateLimiter {
+    max_rate: f64,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    rate: f64,
+    next: Instant,
+    successes: u32,
+}
+
+impl RateLimiter {
+    /// at most `requests_per_second` requests per second,
+    /// anything not positive meaning no limit at all
+    #[must_use]
+    pub fn per_second(requests_per_second: f64) -> Self {
+        let max_rate = if requests_per_second > 0.0 {
+            requests_per_second
+        } else {
+            f64::INFINITY
+        };
+        Self {
+            max_rate,
+            state: Mutex::new(State {
+                rate: max_rate,
+                next: Instant::now(),
+                successes: 0,
+            }),
+        }
+    }
+
+    /// the current rate, in requests per second
+    #[must_use]
+    pub fn requests_per_second(&self) -> f64 {
+        self.state.lock().rate
+    }
+
+    /// waits until the next free slot
+    pub async fn acquire(&self) {
+        let slot = {
+            let mut state = self.state.lock();
+            let slot = state.next.max(Instant::now());
+            let spacing = Duration::try_from_secs_f64(state.rate.recip()).unwrap_or(FOREVER);
+            state.next = later(slot, spacing);
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+
+    /// additively increases the rate after enough successes in a row
+    pub fn on_success(&self) {
+        let increased = {
+            let mut state = self.state.lock();
+            state.successes += 1;
+            if state.successes >= INCREASE_AFTER && state.rate < self.max_rate {
+                state.successes = 0;
+                state.rate = (state.rate + INCREASE_BY).min(self.max_rate);
+                Some(state.rate)
+            } else {
+                None
+            }
+        };
+        if let Some(rate) = increased {
+            info!("going up to {rate:.2} requests per second");
+        }
+    }
+
+    /// multiplicatively decreases the rate, and pauses every
+    /// request until `retry_after` has passed if it's given
+    pub fn on_rate_limited(&self, retry_after: Option<Duration>) {
+        let rate = {
+            let mut state = self.state.lock();
+            state.successes = 0;
+            state.rate = if state.rate.is_finite() {
+                // never faster than configured, even if that's below the floor
+                (state.rate * DECREASE_BY).max(MIN_RATE.min(self.max_rate))
+            } else {
+                // unlimited until now, so start from somewhere sensible
+                1.0
+            };
+            state.rate
+        };
+        if let Some(retry_after) = retry_after {
+            self.pause(retry_after);
+        }
+        info!("rate limited, going down to {rate:.2} requests per second");
+    }
+
+    /// holds back every request until `duration` has passed
+    pub fn pause(&self, duration: Duration) {
+        let mut state = self.state.lock();
+        state.next = state.next.max(later(Instant::now(), duration));
+    }
+}
+
+/// `instant + duration`, or as good as never if that's past what an
+/// instant can hold, e.g. for a `Retry-After` of `u64::MAX` seconds
+fn later(instant: Instant, duration: Duration) -> Instant {
+    instant
+        .checked_add(duration.min(FOREVER))
+        .unwrap_or(instant)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_rate(limiter: &RateLimiter, expected: f64) {
+        let rate = limiter.requests_per_second();
+        assert!((rate - expected).abs() < 1e-9, "{rate} isn't {expected}");
+    }
+
+    #[test]
+    fn rate_limits_halve_the_rate() {
+        let limiter = RateLimiter::per_second(10.0);
+        limiter.on_rate_limited(None);
+        assert_rate(&limiter, 5.0);
+        limiter.on_rate_limited(None);
+        assert_rate(&limiter, 2.5);
+        for _ in 0..20 {
+            limiter.on_rate_limited(None);
+        }
+        assert_rate(&limiter, MIN_RATE);
+    }
+
+    #[test]
+    fn rate_limits_never_speed_up_a_slow_rate() {
+        // slower than the floor already, e.g. a cooldown of 100 seconds
+        let limiter = RateLimiter::per_second(0.01);
+        limiter.on_rate_limited(None);
+        assert_rate(&limiter, 0.01);
+        let limiter = RateLimiter::per_second(0.08);
+        limiter.on_rate_limited(None);
+        assert_rate(&limiter, MIN_RATE);
+    }
+
+    #[test]
+    fn unlimited_starts_over_from_one() {
+        let limiter = RateLimiter::per_second(0.0);
+        assert!(limiter.requests_per_second().is_infinite());
+        limiter.on_rate_limited(None);
+        assert_rate(&limiter, 1.0);
+    }
+
+    #[test]
+    fn successes_raise_the_rate_up_to_where_it_started() {
+        let limiter = RateLimiter::per_second(5.5);
+        limiter.on_rate_limited(None);
+        for _ in 1..INCREASE_AFTER {
+            limiter.on_success();
+        }
+        assert_rate(&limiter, 2.75);
+        limiter.on_success();
+        assert_rate(&limiter, 3.0);
+        for _ in 0..INCREASE_AFTER * 20 {
+            limiter.on_success();
+        }
+        assert_rate(&limiter, 5.5);
+    }
+
+    #[test]
+    fn a_rate_limit_resets_the_successes() {
+        let limiter = RateLimiter::per_second(10.0);
+        limiter.on_rate_limited(None);
+        for _ in 1..INCREASE_AFTER {
+            limiter.on_success();
+        }
+        limiter.on_rate_limited(None);
+        limiter.on_success();
+        assert_rate(&limiter, 2.5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slots_are_spaced_by_the_rate() {
+        let limiter = RateLimiter::per_second(10.0);
+        let start = Instant::now();
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_millis(100));
+        limiter.on_rate_limited(None);
+        limiter.acquire().await;
+        // the slot after a rate limit was handed out at the old rate
+        assert_eq!(start.elapsed(), Duration::from_millis(200));
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn absurd_waits_dont_overflow() {
+        let limiter = RateLimiter::per_second(1e-300);
+        limiter.pause(Duration::MAX);
+        limiter.on_rate_limited(Some(Duration::from_secs(u64::MAX)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retry_after_pauses_every_request() {
+        let limiter = RateLimiter::per_second(10.0);
+        limiter.acquire().await;
+        let start = Instant::now();
+        limiter.on_rate_limited(Some(Duration::from_secs(3)));
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(3));
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_millis(3200));
+    }
+}
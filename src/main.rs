@@ -1,13 +1,19 @@
-use collection::Collection;
-use color_eyre::Result;
-use inquire::{MultiSelect, Select, Text};
-use tracing::info;
+use color_eyre::{eyre::WrapErr, Report, Result};
+use infinite_cookbook::{
+    backup,
+    browser::BrowserSave,
+    checkpoint::Checkpoint,
+    collection::{Collection, ScanMode, ScanOptions},
+    config::Config,
+    diff::Diff,
+    finite_collection::FiniteCollection,
+    shutdown::Shutdown,
+};
+use inquire::{Confirm, MultiSelect, Select, Text};
+use std::path::Path;
+use tracing::{error, info};
 
-pub mod collection;
-pub mod finite_collection;
-pub mod finite_item;
-pub mod item;
-pub mod response;
+const CONFIG: &str = "config.ron";
 
 const INFO: &str = "OVERVIEW: the ♾️📕 infinite cookbook is a helper program for ♾️🛠️ infinite craft by neal agarwal. the original game can be found and played at https://neal.fun/infinite-craft/\n\nVOCABULARY\n\nITEM: a named item/concept/person/etc. and its accompanying emoji as per the website, e.g. 🔥 Fire or 👊 Goku\n\nCOMBINE: to combine is to... combine two items through its recipe\n\nRESEARCH: to research is to discover an item through combining the items of one of its recipes, e.g. to research 💨 Steam through 🔥 Fire and 💧 Water\n\nRECIPE: two items used to research a given item, e.g. 🔥 Fire and 💧 Water is a recipe for 💨 Steam\n\nFEATURES: currently, it supports scanning (iterating over every researched item and combining them), adding custom items, scanning just a list of items, viewing all researched items... and displaying this message :)";
 
@@ -15,28 +21,106 @@ const INFO: &str = "OVERVIEW: the ♾️📕 infinite cookbook is a helper progr
 async fn main() -> Result<()> {
     color_eyre::install()?;
     tracing_subscriber::fmt::init();
-    let mut collection = Collection::open("collection.ron").unwrap_or_default();
-    let choices = vec!["scan", "craft", "add", "help", "view", "quit"];
+    let config = Config::load(CONFIG)?;
+    let shutdown = Shutdown::listen();
+    // a missing save is a new collection, anything else that fails must not
+    // be papered over with the starting items, or the next save replaces it
+    let mut collection = Collection::open(&config.save_path)
+        .wrap_err_with(|| {
+            format!(
+                "couldn't open {}, move it away or put one of its .bak backups in its place",
+                config.save_path
+            )
+        })?
+        .with_config(&config)?
+        .with_shutdown(shutdown.clone());
+    let choices = vec![
+        "scan", "craft", "add", "help", "view", "export", "browser", "diff", "merge", "restore",
+        "quit",
+    ];
+
+    let checkpoint_path = Checkpoint::path(&config.save_path);
+    if let Some(checkpoint) = Checkpoint::open(&checkpoint_path)? {
+        let resume = Confirm::new(&format!(
+            "resume scan session {} ({})?",
+            checkpoint.session, checkpoint.stats
+        ))
+        .with_default(true)
+        .prompt_skippable()?
+        .unwrap_or(false);
+        if resume {
+            let stats = collection.resume(checkpoint, config.concurrency).await?;
+            info!("session finished: {stats}");
+            if !shutdown.is_requested() {
+                collection.save(&config.save_path)?;
+            }
+            shutdown.reset();
+        } else {
+            Checkpoint::remove(&checkpoint_path)?;
+        }
+    }
 
     loop {
         match Select::new("what do?", choices.clone())
             .prompt_skippable()?
             .unwrap_or("")
         {
-            "scan" => loop {
-                let now = std::time::Instant::now();
-                let found = collection.scan().await?;
-                info!("found {found} new items!");
-                info!(
-                    "scan finished in {} seconds ({} minutes)",
-                    now.elapsed().as_secs(),
-                    now.elapsed().as_secs_f64() / 60.0
-                );
-                collection.save("collection.ron")?;
-            },
+            "scan" => {
+                let options = ScanOptions {
+                    mode: scan_mode()?,
+                    concurrency: config.concurrency,
+                };
+                shutdown.reset();
+                loop {
+                    let stats = collection.scan(options).await?;
+                    if shutdown.is_requested() {
+                        info!("scan stopped: {stats}");
+                        shutdown.reset();
+                        break;
+                    }
+                    info!("scan finished: {stats}");
+                    collection.save(&config.save_path)?;
+                    // nothing settled, only pairs that failed or aren't known
+                    // (again) if any, so scanning again would only spin
+                    if stats.combinations == stats.failures + stats.misses {
+                        break;
+                    }
+                }
+            }
+            "craft" => {
+                // a ctrl-c at the menu shouldn't stop this one before it starts
+                shutdown.reset();
+                craft(&collection).await?;
+            }
             "add" => add(&mut collection)?,
             "help" => println!("{INFO}"),
             "view" => view(&collection)?,
+            "export" => export(&collection)?,
+            "browser" => {
+                if let Err(why) = browser(&mut collection) {
+                    report(&why);
+                }
+            }
+            "diff" => {
+                if let Err(why) = diff(&collection, &config) {
+                    report(&why);
+                }
+            }
+            "merge" => {
+                if let Err(why) = merge(&mut collection, &config) {
+                    report(&why);
+                }
+            }
+            "restore" => match restore(&collection, &config) {
+                Ok(true) => {
+                    collection = Collection::open(&config.save_path)?
+                        .with_config(&config)?
+                        .with_shutdown(shutdown.clone());
+                    info!("restored {} items", collection.len());
+                }
+                Ok(false) => {}
+                Err(why) => report(&why),
+            },
             _ => break,
         };
     }
@@ -44,6 +128,40 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// tells what went wrong with a menu choice, which is then simply left,
+/// rather than ending the whole session over e.g. a mistyped path
+fn report(why: &Report) {
+    error!("{why:#}");
+}
+
+fn scan_mode() -> Result<ScanMode> {
+    let mode = Select::new("which pairs?", vec!["frontier", "full"])
+        .with_help_message("frontier only combines items found since the last scan")
+        .prompt_skippable()?;
+    Ok(match mode {
+        Some("full") => ScanMode::Full,
+        _ => ScanMode::Frontier,
+    })
+}
+
+async fn craft(collection: &Collection) -> Result<()> {
+    let mut names = collection
+        .items
+        .iter()
+        .map(|item| item.name())
+        .collect::<Vec<_>>();
+    names.sort_unstable();
+    let Some(first) = Select::new("first?", names.clone()).prompt_skippable()? else {
+        return Ok(());
+    };
+    let Some(second) = Select::new("second?", names).prompt_skippable()? else {
+        return Ok(());
+    };
+    let response = collection.combine_with_retry(&first, &second).await;
+    println!("{}", collection.apply(&first, &second, response));
+    Ok(())
+}
+
 fn view(collection: &Collection) -> Result<()> {
     let items = collection.items.clone().into_read_only();
     let items = items.values().collect();
@@ -54,6 +172,119 @@ fn view(collection: &Collection) -> Result<()> {
     Ok(())
 }
 
+fn export(collection: &Collection) -> Result<()> {
+    let Some(path) = Text::new("export to?")
+        .with_default("collection.export.ron")
+        .prompt_skippable()?
+    else {
+        return Ok(());
+    };
+    collection.export(&path)?;
+    info!("exported {} items to {path}", collection.len());
+    Ok(())
+}
+
+/// moves items between the cookbook and the browser game's
+/// `infinite-craft-data` localStorage entry, through a json file
+fn browser(collection: &mut Collection) -> Result<()> {
+    let Some(direction) = Select::new("browser?", vec!["import", "export"])
+        .with_help_message("copy(localStorage.getItem(\"infinite-craft-data\")) in the game's console copies its save")
+        .prompt_skippable()?
+    else {
+        return Ok(());
+    };
+    let Some(path) = Text::new("json file?")
+        .with_default("infinite-craft-data.json")
+        .prompt_skippable()?
+    else {
+        return Ok(());
+    };
+    if direction == "import" {
+        let save = BrowserSave::from_json(&std::fs::read_to_string(&path)?)?;
+        let added = collection.import_browser(&save);
+        info!("added {added} of {} items from {path}", save.elements.len());
+    } else {
+        // written over the game's own save, everything else in it is kept
+        let save = if Path::new(&path).exists() {
+            BrowserSave::from_json(&std::fs::read_to_string(&path)?)?
+        } else {
+            BrowserSave::default()
+        };
+        std::fs::write(&path, collection.to_browser(&save).to_json()?)?;
+        info!("exported {} items to {path}, paste it into localStorage.setItem(\"infinite-craft-data\", ...) and reload", collection.len());
+    }
+    Ok(())
+}
+
+/// what another collection has that this one doesn't, or what
+/// this one has gained since a backup
+fn diff(collection: &Collection, config: &Config) -> Result<()> {
+    let against =
+        Select::new("diff against?", vec!["another collection", "a backup"]).prompt_skippable()?;
+    let (before, after) = match against {
+        Some("another collection") => {
+            let Some(path) = Text::new("which collection?").prompt_skippable()? else {
+                return Ok(());
+            };
+            (collection.to_finite(), FiniteCollection::open(&path)?)
+        }
+        Some(_) => {
+            let backups = backup::backups(&config.save_path)?;
+            let Some(backup) = Select::new("which backup?", backups).prompt_skippable()? else {
+                return Ok(());
+            };
+            let backup = FiniteCollection::open(&backup.path.to_string_lossy())?;
+            (backup, collection.to_finite())
+        }
+        None => return Ok(()),
+    };
+    let diff = Diff::new(&before, &after);
+    let json = Select::new("as?", vec!["text", "json"]).prompt_skippable()? == Some("json");
+    if json {
+        println!("{}", diff.to_json()?);
+    } else {
+        println!("{diff}");
+    }
+    Ok(())
+}
+
+fn merge(collection: &mut Collection, config: &Config) -> Result<()> {
+    let Some(path) = Text::new("merge which collection in?").prompt_skippable()? else {
+        return Ok(());
+    };
+    let report = collection.merge(&FiniteCollection::open(&path)?);
+    println!("{report}");
+    collection.save(&config.save_path)?;
+    Ok(())
+}
+
+/// whether a backup was restored
+fn restore(collection: &Collection, config: &Config) -> Result<bool> {
+    let backups = backup::backups(&config.save_path)?;
+    if backups.is_empty() {
+        println!("there are no backups of {} yet", config.save_path);
+        return Ok(false);
+    }
+    let Some(backup) = Select::new("restore which backup?", backups).prompt_skippable()? else {
+        return Ok(false);
+    };
+    let confirmed = Confirm::new(&format!(
+        "replace {} with {backup}? the current version is backed up first",
+        config.save_path
+    ))
+    .with_default(false)
+    .prompt_skippable()?
+    .unwrap_or(false);
+    if confirmed {
+        // flushed first, so nothing only in the journal comes back on top of
+        // the backup; without a backup of its own, which could push the
+        // one being restored out of the rotation
+        collection.flush()?;
+        backup::restore(&config.save_path, &backup, config.backups)?;
+    }
+    Ok(confirmed)
+}
+
 fn add(collection: &mut Collection) -> Result<()> {
     let name = Text::new("name?").prompt()?;
     let emoji = Text::new("emoji?")
@@ -0,0 +1,286 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use crate::{
+    attempt::Outcome, finite_collection::FiniteCollection, finite_item::FiniteItem,
+    pair_index::sort_pair_owned,
+};
+
+/// where two collections disagree; the first one wins, except for
+/// `is_new`, which is kept if either says so
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conflict {
+    Emoji {
+        name: String,
+        ours: String,
+        theirs: String,
+    },
+    IsNew {
+        name: String,
+        ours: bool,
+        theirs: bool,
+    },
+    /// the same pair made different things, or something and nothing
+    Result {
+        first: String,
+        second: String,
+        ours: Outcome,
+        theirs: Outcome,
+    },
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Emoji { name, ours, theirs } => {
+                write!(f, "{name} is {ours} here but {theirs} there")
+            }
+            Self::IsNew { name, ours, .. } => {
+                let (yes, no) = if *ours {
+                    ("here", "there")
+                } else {
+                    ("there", "here")
+                };
+                write!(f, "{name} is a first discovery {yes} but not {no}")
+            }
+            Self::Result {
+                first,
+                second,
+                ours,
+                theirs,
+            } => write!(f, "{first} + {second} makes {ours} here but {theirs} there"),
+        }
+    }
+}
+
+/// what merging brought in, and where the collections disagreed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    pub items: usize,
+    pub recipes: usize,
+    pub attempts: usize,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Display for MergeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} new items, {} new recipes, {} new attempts, {} conflicts",
+            self.items,
+            self.recipes,
+            self.attempts,
+            self.conflicts.len()
+        )?;
+        for conflict in &self.conflicts {
+            write!(f, "\n  {conflict}")?;
+        }
+        Ok(())
+    }
+}
+
+/// an item while merging, its recipes deduplicated by sorted pair
+struct Merging {
+    name: String,
+    emoji: String,
+    is_new: bool,
+    parents: Vec<(String, String)>,
+    pairs: HashSet<(String, String)>,
+}
+
+impl Merging {
+    fn new(item: &FiniteItem) -> Self {
+        let mut merging = Self {
+            name: item.name().into(),
+            emoji: item.emoji().into(),
+            is_new: item.is_new(),
+            parents: vec![],
+            pairs: HashSet::new(),
+        };
+        for (first, second) in item.parents() {
+            merging.push_parents(first, second);
+        }
+        merging
+    }
+
+    /// whether the recipe is new
+    fn push_parents(&mut self, first: &str, second: &str) -> bool {
+        if !self.pairs.insert(sort_pair_owned(first, second)) {
+            return false;
+        }
+        self.parents.push((first.into(), second.into()));
+        true
+    }
+
+    fn to_finite(&self) -> FiniteItem {
+        let parents = self
+            .parents
+            .iter()
+            .map(|(first, second)| (first.as_str(), second.as_str()))
+            .collect();
+        FiniteItem::new(&self.name, &self.emoji, self.is_new, parents)
+    }
+}
+
+/// what every pair is known to make, from recipes and attempts alike
+fn results(collection: &FiniteCollection) -> HashMap<(String, String), Outcome> {
+    let mut results = HashMap::new();
+    for item in collection.items() {
+        for (first, second) in item.parents() {
+            results.insert(
+                sort_pair_owned(first, second),
+                Outcome::Found(item.name().into()),
+            );
+        }
+    }
+    for (first, second, outcome) in collection.attempts() {
+        if outcome.is_settled() {
+            results
+                .entry(sort_pair_owned(first, second))
+                .or_insert_with(|| outcome.clone());
+        }
+    }
+    results
+}
+
+/// `ours` with everything from `theirs` it doesn't have yet
+///
+/// that's items, recipes, attempts and unexplored items, the latter
+/// including every item new to `ours`, since it hasn't been combined
+/// with `ours`' items yet
+///
+/// where they disagree, `ours` is kept and the conflict reported; a pair
+/// that made different things keeps both recipes, as both were seen
+#[must_use]
+pub fn merge(
+    ours: &FiniteCollection,
+    theirs: &FiniteCollection,
+) -> (FiniteCollection, MergeReport) {
+    let mut report = MergeReport::default();
+
+    let mut items = ours.items().iter().map(Merging::new).collect::<Vec<_>>();
+    let mut positions = items
+        .iter()
+        .enumerate()
+        .map(|(position, item)| (item.name.clone(), position))
+        .collect::<HashMap<_, _>>();
+    let mut unexplored = ours.unexplored().map_or_else(
+        || {
+            ours.items()
+                .iter()
+                .map(|item| item.name().to_string())
+                .collect()
+        },
+        <[String]>::to_vec,
+    );
+    let mut seen = unexplored.iter().cloned().collect::<HashSet<_>>();
+    let mut explore = |name: &str| {
+        if seen.insert(name.into()) {
+            unexplored.push(name.into());
+        }
+    };
+
+    for item in theirs.items() {
+        let Some(&position) = positions.get(item.name()) else {
+            positions.insert(item.name().into(), items.len());
+            report.items += 1;
+            report.recipes += item.parents().len();
+            items.push(Merging::new(item));
+            explore(item.name());
+            continue;
+        };
+        let merging = &mut items[position];
+        if merging.emoji != item.emoji() {
+            report.conflicts.push(Conflict::Emoji {
+                name: merging.name.clone(),
+                ours: merging.emoji.clone(),
+                theirs: item.emoji().into(),
+            });
+        }
+        if merging.is_new != item.is_new() {
+            report.conflicts.push(Conflict::IsNew {
+                name: merging.name.clone(),
+                ours: merging.is_new,
+                theirs: item.is_new(),
+            });
+            merging.is_new = true;
+        }
+        for (first, second) in item.parents() {
+            if merging.push_parents(first, second) {
+                report.recipes += 1;
+            }
+        }
+    }
+    for name in theirs.unexplored().unwrap_or_default() {
+        explore(name);
+    }
+
+    report.conflicts.extend(conflicting_results(ours, theirs));
+    let attempts = merge_attempts(ours, theirs, &mut report);
+
+    let merged = FiniteCollection::new(
+        items.iter().map(Merging::to_finite).collect(),
+        attempts,
+        Some(unexplored),
+    )
+    .with_created(match (ours.created(), theirs.created()) {
+        (Some(ours), Some(theirs)) => Some(ours.min(theirs)),
+        (ours, theirs) => ours.or(theirs),
+    });
+    (merged, report)
+}
+
+/// every pair `theirs` knows to make something else than `ours` does
+fn conflicting_results(ours: &FiniteCollection, theirs: &FiniteCollection) -> Vec<Conflict> {
+    let our_results = results(ours);
+    let mut conflicting = results(theirs)
+        .into_iter()
+        .filter_map(|(pair, theirs)| {
+            let ours = our_results.get(&pair)?;
+            (*ours != theirs).then(|| (pair, ours.clone(), theirs))
+        })
+        .collect::<Vec<_>>();
+    conflicting.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    conflicting
+        .into_iter()
+        .map(|((first, second), ours, theirs)| Conflict::Result {
+            first,
+            second,
+            ours,
+            theirs,
+        })
+        .collect()
+}
+
+/// `ours`' attempts, plus `theirs`' where `ours` has none or only a failure
+fn merge_attempts(
+    ours: &FiniteCollection,
+    theirs: &FiniteCollection,
+    report: &mut MergeReport,
+) -> Vec<(String, String, Outcome)> {
+    let mut attempts = ours
+        .attempts()
+        .iter()
+        .map(|(first, second, outcome)| (sort_pair_owned(first, second), outcome.clone()))
+        .collect::<HashMap<_, _>>();
+    for (first, second, outcome) in theirs.attempts() {
+        match attempts.get_mut(&sort_pair_owned(first, second)) {
+            None => {
+                attempts.insert(sort_pair_owned(first, second), outcome.clone());
+                report.attempts += 1;
+            }
+            // a failure here doesn't beat an answer there
+            Some(ours) if !ours.is_settled() && outcome.is_settled() => {
+                *ours = outcome.clone();
+                report.attempts += 1;
+            }
+            Some(_) => {}
+        }
+    }
+    attempts
+        .into_iter()
+        .map(|((first, second), outcome)| (first, second, outcome))
+        .collect()
+}
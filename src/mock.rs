@@ -0,0 +1,335 @@
+use color_eyre::Result;
+use dashmap::DashSet;
+use parking_lot::Mutex;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, net::SocketAddr, path::Path, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+
+use crate::pair_index::sort_pair_owned;
+
+const PATH: &str = "/api/infinite-craft/pair";
+
+const CLOUDFLARE_PAGE: &str = "<!DOCTYPE html><html lang=\"en-US\"><head><title>Just a moment...</title></head><body><div id=\"challenge-body-text\">neal.fun needs to review the security of your connection before proceeding.</div><script src=\"/cdn-cgi/challenge-platform/h/b/orchestrate/chl_page/v1\"></script></body></html>";
+
+const WORDS: [&str; 32] = [
+    "Steam", "Mud", "Lava", "Dust", "Plant", "Cloud", "Stone", "Sand", "Glass", "Storm", "Ash",
+    "Smoke", "Tree", "Island", "Volcano", "Lake", "Ocean", "Rain", "Snow", "Ice", "Wave",
+    "Tornado", "Mountain", "Dragon", "Wizard", "Robot", "Castle", "Sun", "Moon", "Star", "Planet",
+    "Life",
+];
+
+const EMOJIS: [&str; 16] = [
+    "💨", "🟫", "🌋", "🌫️", "🌱", "☁️", "🪨", "🏖️", "🥛", "⛈️", "🌲", "🏝️", "🌊", "❄️", "🐉", "⭐",
+];
+
+/// one known recipe, answered exactly as given
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recipe {
+    pub first: String,
+    pub second: String,
+    pub result: String,
+    pub emoji: String,
+}
+
+/// how the mock server behaves, read from a ron file like the
+/// main config; anything missing from it is defaulted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct MockConfig {
+    /// where to listen, port 0 picking any free one
+    pub address: SocketAddr,
+    /// seeds both the generated recipes and the injected failures
+    pub seed: u64,
+    /// answered before anything gets generated
+    pub recipes: Vec<Recipe>,
+    /// whether pairs missing from `recipes` get a generated result
+    /// (or nothing); if not, they always make nothing
+    pub generate: bool,
+    /// share of generated pairs that make nothing
+    pub nothing_rate: f64,
+    /// share of requests answered with a 403
+    pub forbidden_rate: f64,
+    /// share of requests answered with a 429
+    pub rate_limited_rate: f64,
+    /// seconds sent as `Retry-After` with every 429
+    pub retry_after: Option<u64>,
+    /// share of requests answered with a cloudflare challenge page
+    pub cloudflare_rate: f64,
+    /// milliseconds every response is held back
+    pub latency: u64,
+    /// up to this many milliseconds more, randomly
+    pub latency_jitter: u64,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self {
+            address: SocketAddr::from(([127, 0, 0, 1], 3000)),
+            seed: 0,
+            recipes: vec![],
+            generate: true,
+            nothing_rate: 0.2,
+            forbidden_rate: 0.0,
+            rate_limited_rate: 0.0,
+            retry_after: None,
+            cloudflare_rate: 0.0,
+            latency: 0,
+            latency_jitter: 0,
+        }
+    }
+}
+
+impl MockConfig {
+    /// reads the mock config at `path`, or the defaults if there is none
+    /// # Errors
+    /// if the file exists but can't be read or parsed
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let string = std::fs::read_to_string(path)?;
+        Ok(ron::from_str(&string)?)
+    }
+}
+
+/// what the mock answers a single request with
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Reply {
+    Combination {
+        result: String,
+        emoji: String,
+        is_new: bool,
+    },
+    Forbidden,
+    RateLimited(Option<u64>),
+    Cloudflare,
+    NotFound,
+    BadRequest,
+}
+
+/// a local stand-in for neal.fun, speaking the same protocol
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct MockServer {
+    config: MockConfig,
+    recipes: HashMap<(String, String), (String, String)>,
+    /// results handed out so far, to tell whether one is new
+    seen: DashSet<String>,
+    rng: Mutex<fastrand::Rng>,
+}
+
+impl MockServer {
+    #[must_use]
+    pub fn new(config: MockConfig) -> Self {
+        let recipes = config
+            .recipes
+            .iter()
+            .map(|recipe| {
+                (
+                    sort_pair_owned(&recipe.first, &recipe.second),
+                    (recipe.result.clone(), recipe.emoji.clone()),
+                )
+            })
+            .collect();
+        Self {
+            rng: Mutex::new(fastrand::Rng::with_seed(config.seed)),
+            seen: DashSet::new(),
+            recipes,
+            config,
+        }
+    }
+
+    /// binds to the configured address and answers requests in the
+    /// background, returning the address it actually listens on
+    /// # Errors
+    /// if the address can't be bound
+    pub async fn start(self) -> Result<(SocketAddr, JoinHandle<()>)> {
+        let listener = TcpListener::bind(self.config.address).await?;
+        let address = listener.local_addr()?;
+        let handle = tokio::spawn(Arc::new(self).serve(listener));
+        Ok((address, handle))
+    }
+
+    /// answers requests on `listener` forever
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let server = self.clone();
+                    tokio::spawn(async move {
+                        if let Err(why) = server.handle(stream).await {
+                            warn!("mock connection failed: {why}");
+                        }
+                    });
+                }
+                Err(why) => warn!("mock couldn't accept a connection: {why}"),
+            }
+        }
+    }
+
+    /// answers every request on one keep-alive connection
+    async fn handle(&self, stream: TcpStream) -> Result<()> {
+        let (read, mut write) = stream.into_split();
+        let mut read = BufReader::new(read);
+        loop {
+            let mut request_line = String::new();
+            if read.read_line(&mut request_line).await? == 0 {
+                return Ok(());
+            }
+            // skip the headers, requests never have a body
+            loop {
+                let mut header = String::new();
+                if read.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+                    break;
+                }
+            }
+            let reply = self.reply(&request_line);
+            self.delay().await;
+            write.write_all(&to_http(&reply)).await?;
+        }
+    }
+
+    async fn delay(&self) {
+        let jitter = self.rng.lock().u64(0..=self.config.latency_jitter);
+        let delay = self.config.latency + jitter;
+        if delay > 0 {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+        }
+    }
+
+    fn reply(&self, request_line: &str) -> Reply {
+        let mut parts = request_line.split_whitespace();
+        let (Some("GET"), Some(target)) = (parts.next(), parts.next()) else {
+            return Reply::BadRequest;
+        };
+        let Ok(url) = Url::parse(&format!("http://mock{target}")) else {
+            return Reply::BadRequest;
+        };
+        if url.path() != PATH {
+            return Reply::NotFound;
+        }
+        let query = url.query_pairs().collect::<HashMap<_, _>>();
+        let (Some(first), Some(second)) = (query.get("first"), query.get("second")) else {
+            return Reply::BadRequest;
+        };
+
+        {
+            let mut rng = self.rng.lock();
+            if rng.f64() < self.config.forbidden_rate {
+                return Reply::Forbidden;
+            }
+            if rng.f64() < self.config.rate_limited_rate {
+                return Reply::RateLimited(self.config.retry_after);
+            }
+            if rng.f64() < self.config.cloudflare_rate {
+                return Reply::Cloudflare;
+            }
+        }
+
+        let (result, emoji) = self.combine(first, second);
+        info!("mock: {first} + {second} = {emoji} {result}");
+        let is_new = result != "Nothing" && self.seen.insert(result.clone());
+        Reply::Combination {
+            result,
+            emoji,
+            is_new,
+        }
+    }
+
+    /// the recipe for a pair, either from the table or generated
+    /// from a hash of the pair and the seed
+    fn combine(&self, first: &str, second: &str) -> (String, String) {
+        let key = sort_pair_owned(first, second);
+        if let Some(recipe) = self.recipes.get(&key) {
+            return recipe.clone();
+        }
+        if !self.config.generate {
+            return nothing();
+        }
+        let mut rng = fastrand::Rng::with_seed(self.config.seed ^ fnv1a(&key.0, &key.1));
+        if rng.f64() < self.config.nothing_rate {
+            return nothing();
+        }
+        let word = WORDS[rng.usize(..WORDS.len())];
+        let emoji = EMOJIS[rng.usize(..EMOJIS.len())];
+        // plenty of distinct names, but still plenty of repeats
+        let result = match rng.u32(..4) {
+            0 => word.to_string(),
+            _ => format!("{word} {}", rng.u32(..1000)),
+        };
+        (result, emoji.into())
+    }
+}
+
+fn nothing() -> (String, String) {
+    ("Nothing".into(), String::new())
+}
+
+/// a stable hash, unlike the ones in std
+fn fnv1a(first: &str, second: &str) -> u64 {
+    first
+        .bytes()
+        .chain([0])
+        .chain(second.bytes())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+fn to_http(reply: &Reply) -> Vec<u8> {
+    let (status, content_type, extra, body) = match reply {
+        Reply::Combination {
+            result,
+            emoji,
+            is_new,
+        } => (
+            "200 OK",
+            "application/json",
+            String::new(),
+            serde_json::json!({ "result": result, "emoji": emoji, "isNew": is_new }).to_string(),
+        ),
+        Reply::Forbidden => (
+            "403 Forbidden",
+            "text/plain",
+            String::new(),
+            "forbidden".into(),
+        ),
+        Reply::RateLimited(retry_after) => (
+            "429 Too Many Requests",
+            "text/plain",
+            retry_after.map_or_else(String::new, |seconds| format!("Retry-After: {seconds}\r\n")),
+            "too many requests".into(),
+        ),
+        Reply::Cloudflare => (
+            "403 Forbidden",
+            "text/html; charset=UTF-8",
+            "Server: cloudflare\r\ncf-mitigated: challenge\r\n".into(),
+            CLOUDFLARE_PAGE.into(),
+        ),
+        Reply::NotFound => (
+            "404 Not Found",
+            "text/plain",
+            String::new(),
+            "not found".into(),
+        ),
+        Reply::BadRequest => (
+            "400 Bad Request",
+            "text/plain",
+            String::new(),
+            "bad request".into(),
+        ),
+    };
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n{extra}\r\n{body}",
+        body.len()
+    )
+    .into_bytes()
+}
@@ -0,0 +1,84 @@
+use async_trait::async_trait;
+use color_eyre::Result;
+use std::collections::HashMap;
+
+use crate::{
+    attempt::Outcome,
+    backend::CraftBackend,
+    finite_collection::FiniteCollection,
+    pair_index::sort_pair_owned,
+    response::{Response, ResponseFailure, ResponseSuccess},
+};
+
+/// what a pair is known to make
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Known {
+    Item { name: String, emoji: String },
+    Nothing,
+}
+
+/// answers from the recipes of saved collections, without the network;
+/// pairs none of them know are misses
+#[derive(Debug, Default)]
+pub struct OfflineBackend {
+    recipes: HashMap<(String, String), Known>,
+}
+
+impl OfflineBackend {
+    /// # Errors
+    /// if one of the collections can't be opened
+    pub fn open(paths: &[String]) -> Result<Self> {
+        let mut backend = Self::default();
+        for path in paths {
+            backend.learn(&FiniteCollection::open(path)?);
+        }
+        Ok(backend)
+    }
+
+    /// adds every recipe of the collection, and every pair known to make
+    /// nothing; recipes learned earlier win if collections disagree
+    pub fn learn(&mut self, collection: &FiniteCollection) {
+        for item in collection.items() {
+            for (first, second) in item.parents() {
+                self.recipes
+                    .entry(sort_pair_owned(first, second))
+                    .or_insert_with(|| Known::Item {
+                        name: item.name().into(),
+                        emoji: item.emoji().into(),
+                    });
+            }
+        }
+        for (first, second, outcome) in collection.attempts() {
+            if *outcome == Outcome::Nothing {
+                self.recipes
+                    .entry(sort_pair_owned(first, second))
+                    .or_insert(Known::Nothing);
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.recipes.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty()
+    }
+}
+
+#[async_trait]
+impl CraftBackend for OfflineBackend {
+    async fn combine(&self, first: &str, second: &str) -> Result<ResponseSuccess, ResponseFailure> {
+        match self.recipes.get(&sort_pair_owned(first, second)) {
+            Some(Known::Item { name, emoji }) => Ok(ResponseSuccess::Ok(Response::new(
+                name.clone(),
+                Some(emoji.clone()),
+                false,
+            ))),
+            Some(Known::Nothing) => Ok(ResponseSuccess::Nothing),
+            None => Err(ResponseFailure::Miss),
+        }
+    }
+}
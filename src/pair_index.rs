@@ -0,0 +1,57 @@
+use arcstr::ArcStr;
+use dashmap::DashSet;
+
+/// sorts a pair by name, so that `a + b` and `b + a` are the same key
+#[must_use]
+pub fn sort_pair(first: ArcStr, second: ArcStr) -> (ArcStr, ArcStr) {
+    if first <= second {
+        (first, second)
+    } else {
+        (second, first)
+    }
+}
+
+/// `sort_pair` for owned names, e.g. as a key in a `HashMap` of strings
+#[must_use]
+pub fn sort_pair_owned(first: &str, second: &str) -> (String, String) {
+    if first <= second {
+        (first.into(), second.into())
+    } else {
+        (second.into(), first.into())
+    }
+}
+
+/// every pair that doesn't need to be combined again, i.e. the parents
+/// of every item plus every pair that made nothing, for O(1) lookups
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct PairIndex {
+    pairs: DashSet<(ArcStr, ArcStr)>,
+}
+
+impl PairIndex {
+    pub fn insert(&self, first: ArcStr, second: ArcStr) {
+        self.pairs.insert(sort_pair(first, second));
+    }
+
+    /// cloning an `ArcStr` only bumps its refcount, so lookups don't allocate
+    #[must_use]
+    pub fn contains(&self, first: &ArcStr, second: &ArcStr) -> bool {
+        self.pairs
+            .contains(&sort_pair(first.clone(), second.clone()))
+    }
+
+    pub fn clear(&self) {
+        self.pairs.clear();
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
@@ -0,0 +1,117 @@
+use arcstr::ArcStr;
+
+use crate::pair_index::sort_pair;
+
+/// lazily yields unordered pairs of names, each pair once and sorted,
+/// in which at least one name is part of the frontier
+///
+/// the names are sorted up front, so the order is stable between runs
+#[derive(Debug, Clone)]
+pub struct Pairs {
+    names: Vec<ArcStr>,
+    /// indices into `names` of the frontier, in order
+    outer: Vec<usize>,
+    is_outer: Vec<bool>,
+    /// position in `outer`
+    i: usize,
+    /// position in `names`
+    j: usize,
+}
+
+impl Pairs {
+    /// every pair of `names`
+    #[must_use]
+    pub fn full(names: Vec<ArcStr>) -> Self {
+        let frontier = names.clone();
+        Self::frontier(names, &frontier)
+    }
+
+    /// every pair of `names` involving at least one name of `frontier`
+    #[must_use]
+    pub fn frontier(mut names: Vec<ArcStr>, frontier: &[ArcStr]) -> Self {
+        names.sort_unstable();
+        names.dedup();
+        let mut frontier = frontier.to_vec();
+        frontier.sort_unstable();
+        let is_outer = names
+            .iter()
+            .map(|name| frontier.binary_search(name).is_ok())
+            .collect::<Vec<_>>();
+        let outer = is_outer
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &is_outer)| is_outer.then_some(index))
+            .collect();
+        Self {
+            names,
+            outer,
+            is_outer,
+            i: 0,
+            j: 0,
+        }
+    }
+
+    /// continues from a position taken from pairs of the same names
+    #[must_use]
+    pub fn with_position(self, (i, j): (usize, usize)) -> Self {
+        Self { i, j, ..self }
+    }
+
+    #[must_use]
+    pub fn names(&self) -> &[ArcStr] {
+        &self.names
+    }
+
+    /// where the next pair will come from, to continue later on
+    #[must_use]
+    pub const fn position(&self) -> (usize, usize) {
+        (self.i, self.j)
+    }
+
+    /// whether the pair at `first` and `j` is yielded, pairs of
+    /// two frontier names only being yielded from the smaller one
+    fn yields(&self, first: usize, j: usize) -> bool {
+        j >= first || !self.is_outer[j]
+    }
+
+    /// how many pairs are left, without any filtering
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        let Some(&first) = self.outer.get(self.i) else {
+            return 0;
+        };
+        let inner = self.names.len() - self.outer.len();
+        let current = (self.j..self.names.len())
+            .filter(|&j| self.yields(first, j))
+            .count();
+        let rest = (self.i + 1..self.outer.len())
+            .map(|position| self.outer.len() - position + inner)
+            .sum::<usize>();
+        current + rest
+    }
+}
+
+impl Iterator for Pairs {
+    type Item = (ArcStr, ArcStr);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let &first = self.outer.get(self.i)?;
+            let Some(second) = self.names.get(self.j) else {
+                self.i += 1;
+                self.j = 0;
+                continue;
+            };
+            let j = self.j;
+            self.j += 1;
+            if self.yields(first, j) {
+                return Some(sort_pair(self.names[first].clone(), second.clone()));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        (remaining, Some(remaining))
+    }
+}
@@ -22,9 +22,36 @@ pub enum ResponseFailure {
     #[error("a cloudflare error occured")]
     CloudflareError,
     #[error("a timeout occured")]
-    Timeout,
+    Timeout(Option<std::time::Duration>),
     #[error("not allowed was returned")]
     NotAllowed,
+    #[error("an unexpected status was returned: {0}")]
+    UnexpectedStatus(reqwest::StatusCode),
+    #[error("the pair isn't known offline")]
+    Miss,
+    #[error("the scan was interrupted")]
+    Interrupted,
+}
+
+impl ResponseFailure {
+    /// whether trying the same pair again later might succeed
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::NetworkError(_) | Self::CloudflareError | Self::Timeout(_) => true,
+            Self::UnexpectedStatus(status) => status.is_server_error(),
+            Self::ParsingError(_) | Self::NotAllowed | Self::Miss | Self::Interrupted => false,
+        }
+    }
+
+    /// how long the server asked to wait, if it's a 429 that said so
+    #[must_use]
+    pub const fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            Self::Timeout(retry_after) => *retry_after,
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, new)]
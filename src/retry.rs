@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+/// how often and how patiently a failed combination is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct RetryPolicy {
+    /// retries after the first attempt before the pair is skipped
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// how long every request waits after cloudflare got in the way
+    pub cloudflare_pause: Duration,
+}
+
+impl Default for RetryPolicy {
+    // `Duration::from_mins` is too recent to rely on
+    #[allow(clippy::duration_suboptimal_units)]
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            cloudflare_pause: Duration::from_secs(120),
+        }
+    }
+}
+
+impl RetryPolicy {
+    #[must_use]
+    pub const fn new(
+        max_retries: u32,
+        base_delay: Duration,
+        max_delay: Duration,
+        cloudflare_pause: Duration,
+    ) -> Self {
+        Self {
+            max_retries,
+            base_delay,
+            max_delay,
+            cloudflare_pause,
+        }
+    }
+
+    /// exponential backoff with "equal jitter": somewhere between
+    /// half and all of `base_delay * 2^attempt`, capped at `max_delay`
+    #[must_use]
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2_u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let half = exponential / 2;
+        half + half.mul_f64(fastrand::f64())
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::warn;
+
+/// lets ctrl-c (or sigterm) stop a scan gracefully: the first one asks
+/// the scan to stop once its in-flight requests are done, the second
+/// one quits right away
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    requested: Arc<watch::Sender<bool>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self {
+            requested: Arc::new(watch::Sender::new(false)),
+        }
+    }
+}
+
+impl Shutdown {
+    /// a shutdown handle that listens for signals in the background
+    #[must_use]
+    pub fn listen() -> Self {
+        let shutdown = Self::default();
+        let listener = shutdown.clone();
+        tokio::spawn(async move {
+            loop {
+                if wait_for_signal().await.is_err() {
+                    warn!("can't listen for ctrl-c, it will quit right away");
+                    return;
+                }
+                if listener.is_requested() {
+                    warn!("quitting without saving");
+                    std::process::exit(130);
+                }
+                warn!("stopping once the requests in flight are done, press ctrl-c again to quit right away");
+                listener.request();
+            }
+        });
+        shutdown
+    }
+
+    pub fn request(&self) {
+        self.requested.send_replace(true);
+    }
+
+    /// forgets about an earlier request, e.g. before a new scan
+    pub fn reset(&self) {
+        self.requested.send_replace(false);
+    }
+
+    #[must_use]
+    pub fn is_requested(&self) -> bool {
+        *self.requested.borrow()
+    }
+
+    /// waits until a shutdown is requested
+    pub async fn requested(&self) {
+        let mut receiver = self.requested.subscribe();
+        // the sender lives in self, so this can't fail
+        let _ = receiver.wait_for(|&requested| requested).await;
+    }
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() -> std::io::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result,
+        _ = terminate.recv() => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() -> std::io::Result<()> {
+    tokio::signal::ctrl_c().await
+}
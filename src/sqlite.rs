@@ -0,0 +1,283 @@
+use color_eyre::Result;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::collections::HashMap;
+
+use crate::{
+    attempt::Outcome, clock::unix_time, collection::Collection,
+    finite_collection::FiniteCollection, finite_item::FiniteItem, item::Item,
+    pair_index::sort_pair_owned, store::Store,
+};
+
+const SCHEMA: &str = "
+    create table if not exists items (
+        name text primary key,
+        emoji text not null,
+        is_new integer not null
+    );
+    create table if not exists recipes (
+        first text not null,
+        second text not null,
+        result text not null,
+        primary key (first, second, result)
+    );
+    create index if not exists recipes_by_result on recipes (result);
+    create table if not exists attempts (
+        first text not null,
+        second text not null,
+        -- found, nothing or failed
+        outcome text not null,
+        result text,
+        primary key (first, second)
+    );
+    create table if not exists unexplored (
+        name text primary key
+    );
+    create table if not exists meta (
+        key text primary key,
+        value integer not null
+    );
+";
+
+/// a collection kept in a sqlite database, with a table each for items,
+/// recipes, attempted pairs and unexplored items, so it can be queried
+/// without the cookbook
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SqliteStore {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    /// opens the database at `path`, creating it and its tables if needed
+    /// # Errors
+    /// if it can't be opened, or isn't a database
+    pub fn open(path: &str) -> Result<Self> {
+        let connection = Connection::open(path)?;
+        // every combination is its own write, syncing each would crawl
+        connection.pragma_update(None, "journal_mode", "wal")?;
+        connection.pragma_update(None, "synchronous", "normal")?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// writes all of `collection` in one go; items and recipes already
+    /// in the database are kept, attempts are replaced
+    /// # Errors
+    /// if it can't be written, nothing is then
+    pub fn import(&self, collection: &FiniteCollection) -> Result<()> {
+        self.write(|transaction| {
+            for item in collection.items() {
+                insert_item(transaction, item.name(), item.emoji(), item.is_new())?;
+                for (first, second) in item.parents() {
+                    insert_recipe(transaction, first, second, item.name())?;
+                }
+            }
+            for (first, second, outcome) in collection.attempts() {
+                insert_attempt(transaction, first, second, outcome)?;
+            }
+            match collection.unexplored() {
+                Some(unexplored) => {
+                    for name in unexplored {
+                        insert_unexplored(transaction, name)?;
+                    }
+                }
+                None => {
+                    for item in collection.items() {
+                        insert_unexplored(transaction, item.name())?;
+                    }
+                }
+            }
+            let created = collection
+                .created()
+                .unwrap_or_else(|| unix_time().as_secs());
+            transaction.execute(
+                "insert or ignore into meta (key, value) values ('created', ?1)",
+                params![created],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// everything in the database, or the starting items if it's empty
+    /// # Errors
+    /// if it can't be read
+    pub fn load(&self) -> Result<FiniteCollection> {
+        if self.is_empty()? {
+            self.import(&Collection::default().to_finite())?;
+        }
+        self.read(|connection| {
+            let mut parents = HashMap::<String, Vec<(String, String)>>::new();
+            let mut statement = connection.prepare("select first, second, result from recipes")?;
+            let mut rows = statement.query([])?;
+            while let Some(row) = rows.next()? {
+                parents
+                    .entry(row.get(2)?)
+                    .or_default()
+                    .push((row.get(0)?, row.get(1)?));
+            }
+
+            let mut items = vec![];
+            let mut statement = connection.prepare("select name, emoji, is_new from items")?;
+            let mut rows = statement.query([])?;
+            while let Some(row) = rows.next()? {
+                let name = row.get::<_, String>(0)?;
+                let emoji = row.get::<_, String>(1)?;
+                let parents = parents.remove(&name).unwrap_or_default();
+                let parents = parents
+                    .iter()
+                    .map(|(first, second)| (first.as_str(), second.as_str()))
+                    .collect();
+                items.push(FiniteItem::new(&name, &emoji, row.get(2)?, parents));
+            }
+
+            let mut attempts = vec![];
+            let mut statement =
+                connection.prepare("select first, second, outcome, result from attempts")?;
+            let mut rows = statement.query([])?;
+            while let Some(row) = rows.next()? {
+                let outcome = match (
+                    row.get::<_, String>(2)?.as_str(),
+                    row.get::<_, Option<String>>(3)?,
+                ) {
+                    ("found", Some(result)) => Outcome::Found(result.into()),
+                    ("nothing", _) => Outcome::Nothing,
+                    _ => Outcome::Failed,
+                };
+                attempts.push((row.get(0)?, row.get(1)?, outcome));
+            }
+
+            let mut statement = connection.prepare("select name from unexplored")?;
+            let unexplored = statement
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<Vec<String>, _>>()?;
+
+            let created = connection
+                .query_row("select value from meta where key = 'created'", [], |row| {
+                    row.get(0)
+                })
+                .optional()?;
+            Ok(FiniteCollection::new(items, attempts, Some(unexplored)).with_created(created))
+        })
+    }
+
+    fn is_empty(&self) -> Result<bool> {
+        self.read(|connection| {
+            let items = connection
+                .query_row("select count(*) from items", [], |row| row.get::<_, u64>(0))?;
+            Ok(items == 0)
+        })
+    }
+
+    fn read<T>(&self, read: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+        let connection = self.connection.lock();
+        let result = read(&connection);
+        drop(connection);
+        result
+    }
+
+    /// runs `write` in a transaction, which is only committed if it succeeds
+    fn write(&self, write: impl FnOnce(&Transaction) -> Result<()>) -> Result<()> {
+        let mut connection = self.connection.lock();
+        let transaction = connection.transaction()?;
+        write(&transaction)?;
+        transaction.commit()?;
+        drop(connection);
+        Ok(())
+    }
+}
+
+impl Store for SqliteStore {
+    fn combined(
+        &self,
+        first: &str,
+        second: &str,
+        outcome: &Outcome,
+        discovered: Option<&Item>,
+    ) -> Result<()> {
+        self.write(|transaction| {
+            insert_attempt(transaction, first, second, outcome)?;
+            if let Outcome::Found(result) = outcome {
+                insert_recipe(transaction, first, second, result)?;
+            }
+            if let Some(item) = discovered {
+                insert_item(transaction, item.as_str(), item.emoji(), item.is_new())?;
+                insert_unexplored(transaction, item.as_str())?;
+            }
+            Ok(())
+        })
+    }
+
+    fn added(&self, item: &Item) -> Result<()> {
+        self.write(|transaction| {
+            transaction.execute(
+                "insert or replace into items (name, emoji, is_new) values (?1, ?2, ?3)",
+                params![item.as_str(), item.emoji(), item.is_new()],
+            )?;
+            insert_unexplored(transaction, item.as_str())?;
+            Ok(())
+        })
+    }
+
+    fn merged(&self, collection: &FiniteCollection) -> Result<()> {
+        self.import(collection)
+    }
+
+    fn save(&self, collection: &Collection) -> Result<()> {
+        self.write(|transaction| {
+            transaction.execute("delete from unexplored", [])?;
+            for name in collection.unexplored.iter() {
+                insert_unexplored(transaction, &name)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+fn insert_item(transaction: &Transaction, name: &str, emoji: &str, is_new: bool) -> Result<()> {
+    transaction.execute(
+        "insert or ignore into items (name, emoji, is_new) values (?1, ?2, ?3)",
+        params![name, emoji, is_new],
+    )?;
+    Ok(())
+}
+
+/// recipes are stored sorted by name too, so either order is the same one
+fn insert_recipe(transaction: &Transaction, first: &str, second: &str, result: &str) -> Result<()> {
+    let (first, second) = sort_pair_owned(first, second);
+    transaction.execute(
+        "insert or ignore into recipes (first, second, result) values (?1, ?2, ?3)",
+        params![first, second, result],
+    )?;
+    Ok(())
+}
+
+/// attempts are stored sorted by name, like in the collection
+fn insert_attempt(
+    transaction: &Transaction,
+    first: &str,
+    second: &str,
+    outcome: &Outcome,
+) -> Result<()> {
+    let (first, second) = sort_pair_owned(first, second);
+    let (outcome, result) = match outcome {
+        Outcome::Found(result) => ("found", Some(result.as_str())),
+        Outcome::Nothing => ("nothing", None),
+        Outcome::Failed => ("failed", None),
+    };
+    transaction.execute(
+        "insert or replace into attempts (first, second, outcome, result) values (?1, ?2, ?3, ?4)",
+        params![first, second, outcome, result],
+    )?;
+    Ok(())
+}
+
+fn insert_unexplored(transaction: &Transaction, name: &str) -> Result<()> {
+    transaction.execute(
+        "insert or ignore into unexplored (name) values (?1)",
+        params![name],
+    )?;
+    Ok(())
+}
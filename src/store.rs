@@ -0,0 +1,82 @@
+use color_eyre::Result;
+use std::{fmt::Debug, path::Path, sync::Arc};
+
+use crate::{
+    attempt::Outcome, collection::Collection, finite_collection::FiniteCollection, item::Item,
+};
+
+/// keeps a collection on disk as it changes, one combination at a time,
+/// instead of rewriting all of it on every save
+pub trait Store: Debug + Send + Sync {
+    /// a pair was combined; `discovered` is the item it made, if that's new
+    /// # Errors
+    /// if it can't be written
+    fn combined(
+        &self,
+        first: &str,
+        second: &str,
+        outcome: &Outcome,
+        discovered: Option<&Item>,
+    ) -> Result<()>;
+
+    /// an item was added by hand, or imported
+    /// # Errors
+    /// if it can't be written
+    fn added(&self, item: &Item) -> Result<()>;
+
+    /// the whole collection changed at once, e.g. by merging another in
+    /// # Errors
+    /// if it can't be written
+    fn merged(&self, collection: &FiniteCollection) -> Result<()>;
+
+    /// writes whatever isn't written as it happens, e.g. which items
+    /// are still unexplored once a scan is done
+    /// # Errors
+    /// if it can't be written
+    fn save(&self, collection: &Collection) -> Result<()>;
+}
+
+/// whether `path` is a database rather than a saved file
+#[must_use]
+pub fn is_database(path: &str) -> bool {
+    matches!(
+        Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str()),
+        Some("sqlite" | "sqlite3" | "db")
+    )
+}
+
+/// the database at `path` and what's in it, created if it doesn't exist
+/// # Errors
+/// if it can't be opened or read, or sqlite support isn't compiled in
+pub fn open(path: &str) -> Result<(Arc<dyn Store>, FiniteCollection)> {
+    #[cfg(feature = "sqlite")]
+    {
+        let store = crate::sqlite::SqliteStore::open(path)?;
+        let collection = store.load()?;
+        Ok((Arc::new(store), collection))
+    }
+    #[cfg(not(feature = "sqlite"))]
+    Err(color_eyre::eyre::eyre!(
+        "{path} is a database, which needs the sqlite feature"
+    ))
+}
+
+/// writes all of `collection` into the database at `path`, e.g. to move
+/// a saved file over; anything already in it is kept
+/// # Errors
+/// if it can't be opened or written, or sqlite support isn't compiled in
+pub fn import(path: &str, collection: &FiniteCollection) -> Result<()> {
+    #[cfg(feature = "sqlite")]
+    {
+        crate::sqlite::SqliteStore::open(path)?.import(collection)
+    }
+    #[cfg(not(feature = "sqlite"))]
+    {
+        let _ = collection;
+        Err(color_eyre::eyre::eyre!(
+            "{path} is a database, which needs the sqlite feature"
+        ))
+    }
+}
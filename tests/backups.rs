@@ -0,0 +1,129 @@
+mod common;
+
+use color_eyre::{eyre::eyre, Result};
+use common::path_in;
+use infinite_cookbook::{backup, collection::Collection, config::Config};
+use std::{io::Write, path::Path, thread::sleep, time::Duration};
+
+/// writes `contents` to `path` like a save does, keeping `backups`
+fn write(path: &Path, backups: usize, contents: &str) -> Result<()> {
+    backup::write_atomic(path, backups, |file| {
+        file.write_all(contents.as_bytes())?;
+        Ok(())
+    })?;
+    // backups are told apart by the millisecond they were made at
+    sleep(Duration::from_millis(2));
+    Ok(())
+}
+
+fn contents(path: impl AsRef<Path>) -> Result<String> {
+    Ok(std::fs::read_to_string(path)?)
+}
+
+#[test]
+fn keeps_the_newest_backups() -> Result<()> {
+    let directory = tempfile::tempdir()?;
+    let path = directory.path().join("save");
+    for version in 0..5 {
+        write(&path, 3, &version.to_string())?;
+    }
+    assert_eq!(contents(&path)?, "4");
+    let backups = backup::backups(&path)?
+        .iter()
+        .map(|backup| contents(&backup.path))
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(backups, ["3", "2", "1"]);
+    Ok(())
+}
+
+#[test]
+fn prunes_down_to_fewer_backups() -> Result<()> {
+    let directory = tempfile::tempdir()?;
+    let path = directory.path().join("save");
+    for version in 0..4 {
+        write(&path, 3, &version.to_string())?;
+    }
+    write(&path, 1, "4")?;
+    let backups = backup::backups(&path)?;
+    assert_eq!(backups.len(), 1);
+    assert_eq!(contents(&backups[0].path)?, "3");
+    // none at all makes none, but leaves the one there alone
+    write(&path, 0, "5")?;
+    assert_eq!(backup::backups(&path)?, backups);
+    Ok(())
+}
+
+#[test]
+fn a_failed_write_leaves_the_old_version() -> Result<()> {
+    let directory = tempfile::tempdir()?;
+    let path = directory.path().join("save");
+    write(&path, 3, "old")?;
+    let failed = backup::write_atomic(&path, 3, |file| {
+        file.write_all(b"half")?;
+        Err(eyre!("interrupted"))
+    });
+    assert!(failed.is_err());
+    assert_eq!(contents(&path)?, "old");
+    assert!(backup::backups(&path)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn restoring_backs_up_what_it_replaces() -> Result<()> {
+    let directory = tempfile::tempdir()?;
+    let path = directory.path().join("save");
+    for version in 0..3 {
+        write(&path, 2, &version.to_string())?;
+    }
+    let oldest = backup::backups(&path)?.pop().expect("there are backups");
+    assert_eq!(contents(&oldest.path)?, "0");
+    backup::restore(&path, &oldest, 2)?;
+    assert_eq!(contents(&path)?, "0");
+    assert_eq!(contents(&backup::backups(&path)?[0].path)?, "2");
+
+    // only backups of this file can be restored over it
+    let other = directory.path().join("other");
+    write(&other, 1, "a")?;
+    write(&other, 1, "b")?;
+    let theirs = backup::backups(&other)?.pop().expect("there's a backup");
+    assert!(backup::restore(&path, &theirs, 2).is_err());
+    assert_eq!(contents(&path)?, "0");
+    Ok(())
+}
+
+#[test]
+fn restores_the_oldest_backup_of_a_full_rotation() -> Result<()> {
+    let directory = tempfile::tempdir()?;
+    let path = path_in(&directory, "collection.ron");
+    let config = Config {
+        save_path: path.clone(),
+        backups: 3,
+        ..Config::default()
+    };
+    let mut collection = Collection::open(&path)?.with_config(&config)?;
+    for index in 0..5 {
+        collection.add_item(&format!("Item {index}"), "🧪");
+        collection.save(&path)?;
+        // backups are told apart by the millisecond they were made at
+        sleep(Duration::from_millis(2));
+    }
+    let backups = backup::backups(&*path)?;
+    assert_eq!(backups.len(), 3);
+    let oldest = backups.last().cloned().expect("there are backups");
+
+    // only in the journal until it's flushed
+    collection.add_item("Journaled", "📓");
+    collection.flush()?;
+    assert_eq!(backup::backups(&*path)?, backups);
+    backup::restore(&*path, &oldest, config.backups)?;
+
+    let restored = Collection::open(&path)?;
+    assert!(restored.items.contains_key("Item 1"));
+    assert!(!restored.items.contains_key("Item 2"));
+    assert!(!restored.items.contains_key("Journaled"));
+    // what was replaced is a backup itself, journal and all
+    let backups = backup::backups(&*path)?;
+    let replaced = Collection::open(&backups[0].path.to_string_lossy())?;
+    assert!(replaced.items.contains_key("Journaled"));
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use color_eyre::Result;
+use infinite_cookbook::{browser::BrowserSave, collection::Collection};
+use serde_json::{json, Value};
+
+#[test]
+fn round_trips_the_whole_save() -> Result<()> {
+    let original = json!({
+        "elements": [
+            {"text": "Water", "emoji": "💧", "discovered": false},
+            {"text": "Fire", "emoji": "🔥", "discovered": false},
+            {"text": "Wind", "emoji": "🌬️", "discovered": false},
+            {"text": "Earth", "emoji": "🌍", "discovered": false},
+            {"text": "Steam", "emoji": "💨", "discovered": true, "hidden": true},
+        ],
+        "darkMode": true,
+    });
+    let save = BrowserSave::from_json(&original.to_string())?;
+    let mut collection = Collection::default();
+    assert_eq!(collection.import_browser(&save), 1);
+
+    let exported = collection.to_browser(&save).to_json()?;
+    assert_eq!(serde_json::from_str::<Value>(&exported)?, original);
+    Ok(())
+}
+
+#[test]
+fn adds_what_the_game_is_missing_after_its_own_items() -> Result<()> {
+    let save = BrowserSave::from_json(
+        &json!({
+            "elements": [
+                {"text": "Fire", "emoji": "🔥", "discovered": false},
+                {"text": "Smoke"},
+            ],
+        })
+        .to_string(),
+    )?;
+    let mut collection = Collection::default();
+    collection.add_item("Mud", "🟫");
+    assert_eq!(collection.import_browser(&save), 1);
+    assert_eq!(
+        collection
+            .items
+            .get("Smoke")
+            .map(|item| item.emoji().to_string()),
+        Some("❓️".into())
+    );
+
+    let exported = collection.to_browser(&save);
+    let names = exported
+        .elements
+        .iter()
+        .map(|element| element.text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Fire", "Smoke", "Water", "Wind", "Earth", "Mud"]);
+    Ok(())
+}
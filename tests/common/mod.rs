@@ -0,0 +1,70 @@
+//! what the integration tests share, not every one of them uses all of it
+#![allow(dead_code)]
+
+use color_eyre::Result;
+use infinite_cookbook::{
+    backend::HttpBackend,
+    config::Config,
+    mock::{MockConfig, MockServer},
+    retry::RetryPolicy,
+};
+use std::{net::SocketAddr, time::Duration};
+use tempfile::TempDir;
+use tokio::task::JoinHandle;
+
+/// a file in `tests/fixtures`
+pub fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+/// `name` in `directory`, as the cookbook takes paths
+pub fn path_in(directory: &TempDir, name: &str) -> String {
+    directory.path().join(name).to_string_lossy().into_owned()
+}
+
+/// retries quickly enough not to slow the tests down
+pub const fn fast_retries(max_retries: u32) -> RetryPolicy {
+    RetryPolicy::new(
+        max_retries,
+        Duration::from_millis(1),
+        Duration::from_millis(5),
+        Duration::from_millis(5),
+    )
+}
+
+/// a mock server on a free port, stopped once it's dropped,
+/// whether the test passed or not
+pub struct Mock {
+    pub address: SocketAddr,
+    server: JoinHandle<()>,
+}
+
+impl Mock {
+    pub async fn start(config: MockConfig) -> Result<Self> {
+        let (address, server) = MockServer::new(MockConfig {
+            address: SocketAddr::from(([127, 0, 0, 1], 0)),
+            ..config
+        })
+        .start()
+        .await?;
+        Ok(Self { address, server })
+    }
+
+    /// the default config, asking this mock
+    pub fn config(&self) -> Config {
+        Config {
+            url: format!("http://{}/api/infinite-craft/pair", self.address),
+            ..Config::default()
+        }
+    }
+
+    pub fn backend(&self) -> Result<HttpBackend> {
+        HttpBackend::new(&self.config())
+    }
+}
+
+impl Drop for Mock {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
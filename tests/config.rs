@@ -0,0 +1,33 @@
+use infinite_cookbook::config::Config;
+
+#[test]
+fn rejects_unusable_seconds() {
+    for seconds in [-1.0, f64::NAN, f64::INFINITY, 1e30] {
+        for config in [
+            Config {
+                timeout: seconds,
+                ..Config::default()
+            },
+            Config {
+                connect_timeout: seconds,
+                ..Config::default()
+            },
+            Config {
+                cooldown: seconds,
+                ..Config::default()
+            },
+        ] {
+            assert!(config.validate().is_err(), "{config:?}");
+        }
+    }
+}
+
+#[test]
+fn accepts_no_cooldown_at_all() {
+    let config = Config {
+        cooldown: 0.0,
+        ..Config::default()
+    };
+    assert!(config.validate().is_ok());
+    assert!(Config::default().validate().is_ok());
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use color_eyre::Result;
+use common::path_in;
+use infinite_cookbook::{
+    attempt::Outcome,
+    collection::Collection,
+    item::Item,
+    journal::Journal,
+    response::{Response, ResponseSuccess},
+    store::Store,
+};
+
+fn found(name: &str, emoji: &str) -> ResponseSuccess {
+    ResponseSuccess::Ok(Response::new(name.into(), Some(emoji.into()), false))
+}
+
+#[test]
+fn changes_since_the_last_save_survive_a_crash() -> Result<()> {
+    let directory = tempfile::tempdir()?;
+    let path = path_in(&directory, "collection.ron");
+
+    let mut collection = Collection::open(&path)?;
+    collection.apply(&"Fire".into(), &"Water".into(), Ok(found("Steam", "💨")));
+    collection.save(&path)?;
+    collection.apply(&"Steam".into(), &"Water".into(), Ok(found("Cloud", "☁️")));
+    collection.apply(&"Fire".into(), &"Fire".into(), Ok(ResponseSuccess::Nothing));
+    collection.add_item("Salt", "🧂");
+    // killed before saving again
+    drop(collection);
+
+    let reopened = Collection::open(&path)?;
+    let cloud = reopened.items.get("Cloud").expect("cloud was journaled");
+    assert_eq!(cloud.emoji(), "☁️");
+    assert!(cloud.contains_parents("Steam", "Water"));
+    drop(cloud);
+    assert!(reopened.items.contains_key("Salt"));
+    assert!(reopened.items.contains_key("Steam"));
+    assert_eq!(
+        reopened
+            .attempts
+            .get(&("Fire".into(), "Fire".into()))
+            .as_deref(),
+        Some(&Outcome::Nothing)
+    );
+    assert!(reopened.unexplored.contains("Cloud"));
+    Ok(())
+}
+
+#[test]
+fn replaying_an_item_already_saved_keeps_its_recipes() -> Result<()> {
+    let directory = tempfile::tempdir()?;
+    let path = path_in(&directory, "collection.ron");
+
+    let collection = Collection::open(&path)?;
+    collection.apply(&"Fire".into(), &"Water".into(), Ok(found("Steam", "💨")));
+    collection.save(&path)?;
+    drop(collection);
+    // as if the save went through but the journal wasn't emptied after
+    Journal::open(Journal::path(&path))?.added(&Item::new("Steam", "💨", false))?;
+
+    let reopened = Collection::open(&path)?;
+    assert!(reopened
+        .items
+        .get("Steam")
+        .is_some_and(|steam| steam.contains_parents("Fire", "Water")));
+    Ok(())
+}
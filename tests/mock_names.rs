@@ -0,0 +1,62 @@
+mod common;
+
+use color_eyre::Result;
+use common::Mock;
+use infinite_cookbook::{
+    backend::CraftBackend,
+    mock::{MockConfig, Recipe},
+    response::ResponseSuccess,
+};
+
+/// names that break a query string unless they're percent-encoded
+const PAIRS: [(&str, &str); 8] = [
+    ("Salt & Pepper", "Water"),
+    ("Salt", "Pepper & Water"),
+    ("#1", "Fan"),
+    ("What?", "Why?"),
+    ("1 + 1", "2"),
+    ("100%", "Juice"),
+    ("Ice  Cream", " Cone "),
+    ("Crème Brûlée", "火"),
+];
+
+#[tokio::test]
+async fn names_are_attributed_to_the_right_pair() -> Result<()> {
+    let recipes = PAIRS
+        .iter()
+        .enumerate()
+        .map(|(index, (first, second))| Recipe {
+            first: (*first).into(),
+            second: (*second).into(),
+            result: format!("Result {index}"),
+            emoji: "🧪".into(),
+        })
+        .collect();
+    let mock = Mock::start(MockConfig {
+        recipes,
+        generate: false,
+        ..MockConfig::default()
+    })
+    .await?;
+    let backend = mock.backend()?;
+
+    for (index, (first, second)) in PAIRS.into_iter().enumerate() {
+        // either order is the same recipe
+        for (first, second) in [(first, second), (second, first)] {
+            let Ok(ResponseSuccess::Ok(response)) = backend.combine(first, second).await else {
+                panic!("{first} + {second} made nothing or failed");
+            };
+            assert_eq!(
+                response.name(),
+                format!("Result {index}"),
+                "{first} + {second}"
+            );
+        }
+    }
+    // half of a pair is never mistaken for a whole one
+    assert!(matches!(
+        backend.combine("Salt", "Water").await,
+        Ok(ResponseSuccess::Nothing)
+    ));
+    Ok(())
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use color_eyre::Result;
+use common::Mock;
+use infinite_cookbook::{
+    backend::CraftBackend, limiter::RateLimiter, mock::MockConfig, response::ResponseFailure,
+};
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn retry_after_holds_back_the_next_request() -> Result<()> {
+    let mock = Mock::start(MockConfig {
+        rate_limited_rate: 1.0,
+        retry_after: Some(1),
+        ..MockConfig::default()
+    })
+    .await?;
+    let backend = mock.backend()?;
+    let limiter = RateLimiter::per_second(100.0);
+
+    limiter.acquire().await;
+    let why = backend
+        .combine("Fire", "Water")
+        .await
+        .expect_err("every request is rate limited");
+    assert!(
+        matches!(why, ResponseFailure::Timeout(Some(retry_after)) if retry_after == Duration::from_secs(1)),
+        "{why:?}"
+    );
+
+    let start = Instant::now();
+    limiter.on_rate_limited(why.retry_after());
+    limiter.acquire().await;
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    assert!(limiter.requests_per_second() < 100.0);
+    Ok(())
+}
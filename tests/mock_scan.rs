@@ -0,0 +1,102 @@
+mod common;
+
+use color_eyre::Result;
+use common::{fast_retries, path_in, Mock};
+use infinite_cookbook::{
+    attempt::Outcome,
+    cassette::Exchange,
+    collection::{Collection, ScanMode, ScanOptions},
+    config::Config,
+    mock::MockConfig,
+};
+use tempfile::TempDir;
+
+/// the starting items, asking `mock` and recording every exchange in
+/// `cassette`, saving in `directory`
+fn collection(mock: &Mock, directory: &TempDir, cassette: &str) -> Result<Collection> {
+    Ok(Collection::default()
+        .with_config(&Config {
+            save_path: path_in(directory, "mock-scan.ron"),
+            record: Some(cassette.into()),
+            autosave_interval: 0,
+            cooldown: 0.001,
+            ..mock.config()
+        })?
+        .with_retry_policy(fast_retries(20)))
+}
+
+fn is_cloudflare(exchange: &Exchange) -> bool {
+    exchange
+        .headers
+        .iter()
+        .any(|(name, value)| name == "server" && value == "cloudflare")
+}
+
+#[tokio::test]
+async fn scan_survives_a_flaky_server() -> Result<()> {
+    let mock = Mock::start(MockConfig {
+        seed: 7,
+        forbidden_rate: 0.15,
+        rate_limited_rate: 0.25,
+        cloudflare_rate: 0.15,
+        ..MockConfig::default()
+    })
+    .await?;
+    // every request is recorded, to tell what the server answered
+    let directory = tempfile::tempdir()?;
+    let cassette = path_in(&directory, "mock-scan.cassette");
+    let collection = collection(&mock, &directory, &cassette)?;
+    let starting = collection.len();
+
+    // one at a time, so the seeded failures always hit the same requests
+    let stats = collection
+        .scan(ScanOptions {
+            mode: ScanMode::Full,
+            concurrency: 1,
+        })
+        .await?;
+    drop(mock);
+    let exchanges = std::fs::read_to_string(&cassette)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<Vec<Exchange>, _>>()?;
+
+    // every pair of the four starting items, each once, none left out
+    let pairs = starting * (starting + 1) / 2;
+    assert_eq!(stats.combinations, pairs);
+    assert_eq!(collection.attempts.len(), pairs);
+
+    // 429s and cloudflare are retried until they go through,
+    // 403s aren't and are the only failures
+    let rate_limited = exchanges.iter().filter(|exchange| exchange.status == 429);
+    let cloudflare = exchanges.iter().filter(|exchange| is_cloudflare(exchange));
+    let forbidden = exchanges
+        .iter()
+        .filter(|exchange| exchange.status == 403 && !is_cloudflare(exchange));
+    let (rate_limited, cloudflare, forbidden) =
+        (rate_limited.count(), cloudflare.count(), forbidden.count());
+    assert!(rate_limited > 0, "nothing was rate limited");
+    assert!(cloudflare > 0, "cloudflare never got in the way");
+    assert_eq!(exchanges.len(), pairs + rate_limited + cloudflare);
+    let outcomes = collection
+        .attempts
+        .iter()
+        .map(|attempt| attempt.value().clone())
+        .collect::<Vec<_>>();
+    let count = |expected: &Outcome| outcomes.iter().filter(|o| *o == expected).count();
+    let failed = count(&Outcome::Failed);
+    assert!(forbidden > 0, "no request was forbidden");
+    assert_eq!(failed, forbidden);
+    assert_eq!(stats.failures, failed);
+    assert_eq!(stats.nothing, count(&Outcome::Nothing));
+    for outcome in &outcomes {
+        if let Outcome::Found(name) = outcome {
+            assert!(
+                collection.items.contains_key(name.as_str()),
+                "{name} is missing"
+            );
+        }
+    }
+    assert_eq!(collection.len(), starting + stats.discoveries);
+    Ok(())
+}
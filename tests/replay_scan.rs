@@ -0,0 +1,70 @@
+mod common;
+
+use color_eyre::Result;
+use common::{fast_retries, fixture, path_in};
+use infinite_cookbook::{
+    attempt::Outcome,
+    collection::{Collection, ScanMode, ScanOptions},
+    config::{BackendConfig, Config},
+    item::Item,
+};
+
+#[tokio::test]
+async fn scan_replays_retries_in_order() -> Result<()> {
+    let directory = tempfile::tempdir()?;
+    let collection = Collection::from_items(vec![
+        Item::new("Water", "💧", false),
+        Item::new("Fire", "🔥", false),
+    ])
+    .with_config(&Config {
+        backend: BackendConfig::Replay(fixture("replay.cassette")),
+        save_path: path_in(&directory, "replay-scan.ron"),
+        autosave_interval: 0,
+        cooldown: 0.001,
+        ..Config::default()
+    })?
+    // two 429s need two retries, one fewer would give up
+    .with_retry_policy(fast_retries(2));
+
+    let stats = collection
+        .scan(ScanOptions {
+            mode: ScanMode::Full,
+            concurrency: 1,
+        })
+        .await?;
+
+    assert_eq!(stats.combinations, 3);
+    assert_eq!(stats.discoveries, 1);
+    assert_eq!(stats.nothing, 1);
+    assert_eq!(stats.failures, 0);
+    assert_eq!(stats.misses, 1);
+
+    // the 429s were retried until the recorded 200
+    let steam = collection.items.get("Steam").expect("steam was found");
+    assert_eq!(steam.emoji(), "💨");
+    assert!(steam.contains_parents("Fire", "Water"));
+    drop(steam);
+    assert_eq!(
+        collection
+            .attempts
+            .get(&("Fire".into(), "Water".into()))
+            .as_deref(),
+        Some(&Outcome::Found("Steam".into()))
+    );
+    assert_eq!(
+        collection
+            .attempts
+            .get(&("Water".into(), "Water".into()))
+            .as_deref(),
+        Some(&Outcome::Nothing)
+    );
+    // never recorded, so it's a miss, which isn't remembered at all
+    assert!(!collection
+        .attempts
+        .contains_key(&("Fire".into(), "Fire".into())));
+    // and fire stays unexplored until it's known, unlike water
+    assert!(collection.unexplored.contains("Fire"));
+    assert!(!collection.unexplored.contains("Water"));
+    assert!(collection.unexplored.contains("Steam"));
+    Ok(())
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use color_eyre::Result;
+use common::{fixture, path_in};
+use infinite_cookbook::{attempt::Outcome, envelope::Format, finite_collection::FiniteCollection};
+
+/// what every fixture holds, whatever version it's saved as
+fn assert_contents(collection: &FiniteCollection) {
+    let items = collection
+        .items()
+        .iter()
+        .map(|item| (item.name(), item.emoji(), item.parents()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        items,
+        [
+            ("Water", "💧", vec![]),
+            ("Fire", "🔥", vec![]),
+            ("Steam", "💨", vec![("Fire", "Water")]),
+        ]
+    );
+    assert_eq!(
+        collection.attempts(),
+        [
+            (
+                "Fire".into(),
+                "Water".into(),
+                Outcome::Found("Steam".into())
+            ),
+            ("Fire".into(), "Fire".into(), Outcome::Nothing),
+        ]
+    );
+}
+
+#[test]
+fn opens_v0_ron() -> Result<()> {
+    let collection = FiniteCollection::open(&fixture("v0.ron"))?;
+    assert_contents(&collection);
+    // from before frontier scans, so everything counts as unexplored
+    assert_eq!(collection.unexplored(), None);
+    // nothing tells when it was created, so it's given a time on upgrade
+    assert!(collection.created().is_some_and(|created| created > 0));
+    Ok(())
+}
+
+#[test]
+fn opens_v1_ron() -> Result<()> {
+    let collection = FiniteCollection::open(&fixture("v1.ron"))?;
+    assert_contents(&collection);
+    assert_eq!(collection.unexplored(), Some(&["Steam".to_string()][..]));
+    assert_eq!(collection.created(), Some(1_700_000_000));
+    Ok(())
+}
+
+#[test]
+fn opens_v1_bincode() -> Result<()> {
+    let collection = FiniteCollection::open(&fixture("v1.bincode"))?;
+    assert_contents(&collection);
+    assert_eq!(collection.unexplored(), Some(&["Steam".to_string()][..]));
+    assert_eq!(collection.created(), Some(1_700_000_000));
+    Ok(())
+}
+
+#[test]
+fn rejects_newer_versions() {
+    let why = FiniteCollection::open(&fixture("v2.ron"))
+        .expect_err("a newer version shouldn't open")
+        .to_string();
+    assert!(why.contains("format version 2"), "{why}");
+    assert!(why.contains("please update"), "{why}");
+}
+
+#[test]
+fn compressed_saves_open_again() -> Result<()> {
+    let directory = tempfile::tempdir()?;
+    let original = FiniteCollection::open(&fixture("v1.ron"))?;
+    for format in [Format::Ron, Format::Bincode] {
+        let path = path_in(&directory, &format!("{format:?}"));
+        original.save(&path, format, 0)?;
+        let reopened = FiniteCollection::open(&path)?;
+        assert_contents(&reopened);
+        assert_eq!(reopened.created(), original.created());
+    }
+    Ok(())
+}